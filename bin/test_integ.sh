@@ -50,4 +50,26 @@ for grammar_file in integration/test_error/*.grammar; do
 
 done
 
+for grammar_file in integration/test_earley/*.grammar; do
+  echo "grammar parse --earley $grammar_file"
+  set +e
+  output=$(grammar parse --earley "$grammar_file" "${grammar_file%.*}.input" 2>&1)
+  exit_code="$?"
+  set -e
 
+  expected=$(cat "${grammar_file%.*}.exit")
+  if [[ "$exit_code" != "$expected" ]]
+  then
+     echo "Exit code"
+     echo "  actual: $exit_code"
+     echo "  expected: $expected"
+     exit 1
+  fi
+
+  expected=$(cat "${grammar_file%.*}.error")
+  if [[ "$output" != "$expected" ]]
+  then
+     echo "error message differs"
+     exit 1
+  fi
+done
@@ -0,0 +1,416 @@
+use super::{CharacterClass, Expression, ExpressionKind, Grammar};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+/// Terminal symbol used in FIRST and FOLLOW sets.
+///
+/// Literals, character classes and negated expressions are all considered as
+/// single terminals, identified by their value.
+/// Two different terminals can still start with the same char (see [`Analysis::overlaps`]).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Terminal {
+    Literal(String),
+    Regex(String),
+    Negate(String),
+    Eof,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Analysis {
+    pub nullable: HashSet<String>,
+    pub first: HashMap<String, BTreeSet<Terminal>>,
+    pub follow: HashMap<String, BTreeSet<Terminal>>,
+    // chars that can start each terminal (but Eof), as sorted disjoint ranges
+    chars: HashMap<Terminal, Vec<(u32, u32)>>,
+}
+
+const MAX_CHAR: u32 = char::MAX as u32;
+
+impl Grammar {
+    /// Computes the nullable rules, and the FIRST and FOLLOW sets of every rule.
+    ///
+    /// The start rule is followed by [`Terminal::Eof`].
+    pub fn analyze(&self, start: &str) -> Analysis {
+        let rules = self.get_rules();
+        let mut analysis = Analysis {
+            nullable: self.nullable_rules(),
+            first: HashMap::new(),
+            follow: HashMap::new(),
+            chars: HashMap::new(),
+        };
+        for rule in &rules {
+            analysis.first.insert(rule.id.clone(), BTreeSet::new());
+            analysis.follow.insert(rule.id.clone(), BTreeSet::new());
+            add_chars(&rule.expression, &mut analysis.chars);
+        }
+
+        loop {
+            let mut changed = false;
+            for rule in &rules {
+                let first = analysis.first_of(&rule.expression);
+                let current = analysis.first.get_mut(&rule.id).unwrap();
+                if !first.is_subset(current) {
+                    current.extend(first);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        if let Some(follow) = analysis.follow.get_mut(start) {
+            follow.insert(Terminal::Eof);
+        }
+        loop {
+            let mut follow = analysis.follow.clone();
+            for rule in &rules {
+                let trailer = analysis.follow[&rule.id].clone();
+                analysis.add_follow(&rule.expression, trailer, &mut follow);
+            }
+            if follow == analysis.follow {
+                break;
+            }
+            analysis.follow = follow;
+        }
+        analysis
+    }
+}
+
+impl Analysis {
+    /// Returns true if the expression can match the empty string.
+    pub fn is_nullable(&self, expression: &Expression) -> bool {
+        expression.is_nullable(&self.nullable)
+    }
+
+    /// Returns the terminals that can start the expression.
+    pub fn first_of(&self, expression: &Expression) -> BTreeSet<Terminal> {
+        match &expression.kind {
+            ExpressionKind::Choice(expressions) => {
+                expressions.iter().flat_map(|e| self.first_of(e)).collect()
+            }
+            ExpressionKind::Sequence(expressions) => self.first_of_sequence(expressions),
+            ExpressionKind::Group(expression) | ExpressionKind::Quantifier(expression, _) => {
+                self.first_of(expression)
+            }
+            ExpressionKind::Negate(expression) => {
+                BTreeSet::from([Terminal::Negate(expression.to_source())])
+            }
+            ExpressionKind::Literal(value) => {
+                if value.is_empty() {
+                    BTreeSet::new()
+                } else {
+                    BTreeSet::from([Terminal::Literal(value.clone())])
+                }
+            }
+            ExpressionKind::Regex(class) => BTreeSet::from([Terminal::Regex(class.to_string())]),
+            ExpressionKind::NonTerminal(id) => self.first.get(id).cloned().unwrap_or_default(),
+        }
+    }
+
+    /// Returns true if the two terminals can start with the same char,
+    /// for instance `[a-z]` and `"if"`.
+    pub fn overlaps(&self, terminal: &Terminal, other: &Terminal) -> bool {
+        match (self.chars.get(terminal), self.chars.get(other)) {
+            (Some(chars), Some(other_chars)) => intersects(chars, other_chars),
+            _ => terminal == other,
+        }
+    }
+
+    /// Returns the terminals of each set that can start with the same char
+    /// as a terminal of the other set.
+    pub fn overlapping(
+        &self,
+        terminals: &BTreeSet<Terminal>,
+        others: &BTreeSet<Terminal>,
+    ) -> BTreeSet<Terminal> {
+        let mut overlapping = BTreeSet::new();
+        for terminal in terminals {
+            for other in others {
+                if self.overlaps(terminal, other) {
+                    overlapping.insert(terminal.clone());
+                    overlapping.insert(other.clone());
+                }
+            }
+        }
+        overlapping
+    }
+
+    /// Returns the terminals that can start the sequence of expressions.
+    pub fn first_of_sequence(&self, expressions: &[Expression]) -> BTreeSet<Terminal> {
+        let mut first = BTreeSet::new();
+        for expression in expressions {
+            first.extend(self.first_of(expression));
+            if !self.is_nullable(expression) {
+                break;
+            }
+        }
+        first
+    }
+
+    // add the terminals that can follow each non-terminal of the expression,
+    // trailer being the terminals that can follow the expression itself
+    fn add_follow(
+        &self,
+        expression: &Expression,
+        trailer: BTreeSet<Terminal>,
+        follow: &mut HashMap<String, BTreeSet<Terminal>>,
+    ) {
+        match &expression.kind {
+            ExpressionKind::Choice(expressions) => {
+                for expression in expressions {
+                    self.add_follow(expression, trailer.clone(), follow);
+                }
+            }
+            ExpressionKind::Sequence(expressions) => {
+                let mut trailer = trailer;
+                for expression in expressions.iter().rev() {
+                    self.add_follow(expression, trailer.clone(), follow);
+                    let mut first = self.first_of(expression);
+                    if self.is_nullable(expression) {
+                        first.extend(trailer);
+                    }
+                    trailer = first;
+                }
+            }
+            ExpressionKind::Group(expression) => self.add_follow(expression, trailer, follow),
+            ExpressionKind::Quantifier(expression, quantifier) => {
+                let mut trailer = trailer;
+                if quantifier.max() != Some(1) {
+                    trailer.extend(self.first_of(expression));
+                }
+                self.add_follow(expression, trailer, follow)
+            }
+            ExpressionKind::Negate(_) | ExpressionKind::Literal(_) | ExpressionKind::Regex(_) => {}
+            ExpressionKind::NonTerminal(id) => {
+                if let Some(terminals) = follow.get_mut(id) {
+                    terminals.extend(trailer);
+                }
+            }
+        }
+    }
+}
+
+// records the chars that can start each terminal of the expression
+fn add_chars(expression: &Expression, chars: &mut HashMap<Terminal, Vec<(u32, u32)>>) {
+    match &expression.kind {
+        ExpressionKind::Choice(expressions) | ExpressionKind::Sequence(expressions) => {
+            for expression in expressions {
+                add_chars(expression, chars);
+            }
+        }
+        ExpressionKind::Group(expression) | ExpressionKind::Quantifier(expression, _) => {
+            add_chars(expression, chars)
+        }
+        ExpressionKind::Negate(negated) => {
+            let terminal = Terminal::Negate(negated.to_source());
+            chars.insert(terminal, complement(&blocked_chars(negated)));
+            add_chars(negated, chars);
+        }
+        ExpressionKind::Literal(value) => {
+            if let Some(c) = value.chars().next() {
+                chars.insert(Terminal::Literal(value.clone()), vec![(c as u32, c as u32)]);
+            }
+        }
+        ExpressionKind::Regex(class) => {
+            chars.insert(Terminal::Regex(class.to_string()), class_chars(class));
+        }
+        ExpressionKind::NonTerminal(_) => {}
+    }
+}
+
+fn class_chars(class: &CharacterClass) -> Vec<(u32, u32)> {
+    let ranges = normalize(
+        class
+            .ranges()
+            .into_iter()
+            .filter(|(start, end)| start <= end)
+            .map(|(start, end)| (start as u32, end as u32))
+            .collect(),
+    );
+    if class.negated {
+        complement(&ranges)
+    } else {
+        ranges
+    }
+}
+
+// chars at which the expression surely matches, so that its negation cannot start with them
+fn blocked_chars(expression: &Expression) -> Vec<(u32, u32)> {
+    match &expression.kind {
+        ExpressionKind::Literal(value) if value.chars().count() == 1 => {
+            let c = value.chars().next().unwrap() as u32;
+            vec![(c, c)]
+        }
+        ExpressionKind::Regex(class)
+            if class
+                .quantifier
+                .as_ref()
+                .is_none_or(|quantifier| quantifier.min() > 0) =>
+        {
+            class_chars(class)
+        }
+        ExpressionKind::Group(expression) => blocked_chars(expression),
+        ExpressionKind::Choice(expressions) => {
+            normalize(expressions.iter().flat_map(blocked_chars).collect())
+        }
+        _ => vec![],
+    }
+}
+
+// sorts and merges the ranges
+fn normalize(mut ranges: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+    ranges.sort();
+    let mut merged: Vec<(u32, u32)> = vec![];
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+// `ranges` being normalized
+fn complement(ranges: &[(u32, u32)]) -> Vec<(u32, u32)> {
+    let mut complement = vec![];
+    let mut next = 0;
+    for (start, end) in ranges {
+        if *start > next {
+            complement.push((next, start - 1));
+        }
+        next = end + 1;
+    }
+    if next <= MAX_CHAR {
+        complement.push((next, MAX_CHAR));
+    }
+    complement
+}
+
+fn intersects(ranges: &[(u32, u32)], others: &[(u32, u32)]) -> bool {
+    ranges
+        .iter()
+        .any(|(start, end)| others.iter().any(|(s, e)| s <= end && start <= e))
+}
+
+impl fmt::Display for Terminal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Terminal::Literal(value) => {
+                write!(f, "{}", ExpressionKind::Literal(value.clone()).to_source())
+            }
+            Terminal::Regex(value) => write!(f, "{}", value),
+            Terminal::Negate(value) => write!(f, "~{}", value),
+            Terminal::Eof => write!(f, "<eof>"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn terminals(values: &[Terminal]) -> BTreeSet<Terminal> {
+        values.iter().cloned().collect()
+    }
+
+    fn literal(value: &str) -> Terminal {
+        Terminal::Literal(value.to_string())
+    }
+
+    #[test]
+    fn test_analyze() {
+        let g = parse(
+            "# General\n\
+             expr: term (\"+\" term)*\n\
+             term: factor? [0-9]+ | ~\"x\"\n\
+             factor: \"-\" | \"\"\n",
+        )
+        .unwrap();
+        let analysis = g.analyze("expr");
+
+        assert!(analysis.nullable.contains("factor"));
+        assert!(!analysis.nullable.contains("term"));
+
+        assert_eq!(
+            analysis.first["expr"],
+            terminals(&[
+                literal("-"),
+                Terminal::Regex("[0-9]+".to_string()),
+                Terminal::Negate("\"x\"".to_string()),
+            ])
+        );
+        assert_eq!(analysis.first["factor"], terminals(&[literal("-")]));
+
+        assert_eq!(analysis.follow["expr"], terminals(&[Terminal::Eof]));
+        assert_eq!(
+            analysis.follow["term"],
+            terminals(&[literal("+"), Terminal::Eof])
+        );
+        assert_eq!(
+            analysis.follow["factor"],
+            terminals(&[Terminal::Regex("[0-9]+".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_follow_recursive() {
+        let g = parse("# General\na: b \"x\"\nb: \"y\" b?\n").unwrap();
+        let analysis = g.analyze("a");
+        assert_eq!(analysis.follow["b"], terminals(&[literal("x")]));
+    }
+
+    #[test]
+    fn test_start() {
+        let g = parse("# General\na: b \"x\"\nb: \"y\"\n").unwrap();
+        let analysis = g.analyze("b");
+        assert!(analysis.follow["a"].is_empty());
+        assert_eq!(
+            analysis.follow["b"],
+            terminals(&[literal("x"), Terminal::Eof])
+        );
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let g = parse(
+            "# General\n\
+             a: [a-z] | [a-z]+ | \"a\" | \"ab\" | \"b\" | [0-9] | \"5\" | ~[a-z]\n\
+             b: ~\"\\n\" | \"\\n\" | ~\"ab\"\n",
+        )
+        .unwrap();
+        let analysis = g.analyze("a");
+        let regex = |value: &str| Terminal::Regex(value.to_string());
+        let negate = |value: &str| Terminal::Negate(value.to_string());
+
+        assert!(analysis.overlaps(&regex("[a-z]"), &regex("[a-z]+")));
+        assert!(analysis.overlaps(&literal("a"), &literal("ab")));
+        assert!(analysis.overlaps(&regex("[0-9]"), &literal("5")));
+        assert!(analysis.overlaps(&regex("~[a-z]"), &literal("5")));
+        assert!(!analysis.overlaps(&literal("a"), &literal("b")));
+        assert!(!analysis.overlaps(&regex("~[a-z]"), &literal("ab")));
+
+        assert!(!analysis.overlaps(&negate("\"\\n\""), &literal("\n")));
+        assert!(analysis.overlaps(&negate("\"\\n\""), &literal("a")));
+        assert!(analysis.overlaps(&negate("\"ab\""), &literal("a")));
+
+        assert!(analysis.overlaps(&Terminal::Eof, &Terminal::Eof));
+        assert!(!analysis.overlaps(&Terminal::Eof, &literal("a")));
+
+        assert_eq!(
+            analysis.overlapping(
+                &terminals(&[literal("ab"), literal("b")]),
+                &terminals(&[regex("[a-z]+"), literal("5")])
+            ),
+            terminals(&[literal("ab"), literal("b"), regex("[a-z]+")])
+        );
+    }
+
+    #[test]
+    fn test_terminal_display() {
+        assert_eq!(literal("\"").to_string(), r#""\"""#);
+        assert_eq!(Terminal::Negate("[a]".to_string()).to_string(), "~[a]");
+        assert_eq!(Terminal::Eof.to_string(), "<eof>");
+    }
+}
@@ -1,47 +1,93 @@
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Quantifier {
-    ZeroOrOne, // ?
-    OneOrMany, // +
-    Many,      // *
+    ZeroOrOne,                                 // ?
+    OneOrMany,                                 // +
+    Many,                                      // *
+    Repeat { min: usize, max: Option<usize> }, // {m}, {m,} or {m,n}
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Span {
     pub start: usize,
     pub end: usize,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Grammar {
     pub rulesets: Vec<RuleSet>,
+    pub includes: Vec<Include>,
 }
 
+/// Include directive, for instance `@include "lexical.grammar"`.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Include {
+    pub span: Span,
+    pub path: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RuleSet {
     pub comment: Comment,
+    pub prose: Option<Prose>,
     pub rules: Vec<Rule>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Comment {
     pub span: Span,
     pub value: String,
+    // 1 for `#`, 2 for `##`...
+    pub level: usize,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rule {
     pub span: Span,
     pub id: String,
     pub expression: Expression,
+    pub examples: Vec<Example>,
+    pub doc: Vec<DocComment>,
+}
+
+/// Free text between a section heading and its rules, written in light Markdown.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Prose {
+    pub span: Span,
+    pub value: String,
+}
+
+/// Doc comment line of the following rule, for instance `#: A decimal number`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DocComment {
+    pub span: Span,
+    pub value: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Example {
+    pub span: Span,
+    pub value: String,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Expression {
     pub span: Span,
     pub kind: ExpressionKind,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ExpressionKind {
     Choice(Vec<Expression>),
     Sequence(Vec<Expression>),
@@ -49,16 +95,111 @@ pub enum ExpressionKind {
     Negate(Box<Expression>),
     Quantifier(Box<Expression>, Quantifier),
     Literal(String),
-    Regex(String),
+    Regex(CharacterClass),
     NonTerminal(String),
 }
 
+/// Character class, for instance `~[a-z\]]+`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CharacterClass {
+    pub negated: bool,
+    pub items: Vec<ClassItem>,
+    pub quantifier: Option<Quantifier>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClassItem {
+    pub span: Span,
+    pub kind: ClassItemKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClassItemKind {
+    Single(ClassChar),           // a
+    Range(ClassChar, ClassChar), // a-z
+}
+
+/// Char of a character class, as written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClassChar {
+    Char(char),
+    Escape(char), // the char following the backslash
+}
+
+impl Quantifier {
+    /// Returns the minimum number of repetitions.
+    pub fn min(&self) -> usize {
+        match self {
+            Quantifier::ZeroOrOne | Quantifier::Many => 0,
+            Quantifier::OneOrMany => 1,
+            Quantifier::Repeat { min, .. } => *min,
+        }
+    }
+
+    /// Returns the maximum number of repetitions, `None` if unbounded.
+    pub fn max(&self) -> Option<usize> {
+        match self {
+            Quantifier::ZeroOrOne => Some(1),
+            Quantifier::OneOrMany | Quantifier::Many => None,
+            Quantifier::Repeat { max, .. } => *max,
+        }
+    }
+}
+
 impl std::fmt::Display for Quantifier {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Quantifier::ZeroOrOne => write!(f, "?"),
             Quantifier::OneOrMany => write!(f, "+"),
             Quantifier::Many => write!(f, "*"),
+            Quantifier::Repeat { min, max: None } => write!(f, "{{{},}}", min),
+            Quantifier::Repeat {
+                min,
+                max: Some(max),
+            } if min == max => write!(f, "{{{}}}", min),
+            Quantifier::Repeat {
+                min,
+                max: Some(max),
+            } => write!(f, "{{{},{}}}", min, max),
+        }
+    }
+}
+
+impl std::fmt::Display for CharacterClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.negated {
+            write!(f, "~")?;
+        }
+        write!(f, "[")?;
+        for item in &self.items {
+            write!(f, "{}", item.kind)?;
+        }
+        write!(f, "]")?;
+        if let Some(quantifier) = &self.quantifier {
+            write!(f, "{}", quantifier)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for ClassItemKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ClassItemKind::Single(c) => write!(f, "{}", c),
+            ClassItemKind::Range(start, end) => write!(f, "{}-{}", start, end),
+        }
+    }
+}
+
+impl std::fmt::Display for ClassChar {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ClassChar::Char(c) => write!(f, "{}", c),
+            ClassChar::Escape(c) => write!(f, "\\{}", c),
         }
     }
 }
@@ -0,0 +1,201 @@
+use super::{
+    CharacterClass, ClassChar, ClassItem, ClassItemKind, Expression, ExpressionKind, Grammar, Span,
+    ValidateError,
+};
+
+impl Grammar {
+    /// Returns an error for each empty character class, reversed range,
+    /// unknown escape and duplicated character.
+    pub(crate) fn character_class_errors(&self) -> Vec<ValidateError> {
+        self.get_rules()
+            .iter()
+            .flat_map(|rule| rule.expression.character_class_errors())
+            .collect()
+    }
+}
+
+impl Expression {
+    fn character_class_errors(&self) -> Vec<ValidateError> {
+        match &self.kind {
+            ExpressionKind::Choice(expressions) | ExpressionKind::Sequence(expressions) => {
+                expressions
+                    .iter()
+                    .flat_map(|e| e.character_class_errors())
+                    .collect()
+            }
+            ExpressionKind::Group(expression)
+            | ExpressionKind::Negate(expression)
+            | ExpressionKind::Quantifier(expression, _) => expression.character_class_errors(),
+            ExpressionKind::Regex(class) => class.errors(&self.span),
+            ExpressionKind::Literal(_) | ExpressionKind::NonTerminal(_) => vec![],
+        }
+    }
+}
+
+impl CharacterClass {
+    /// Returns the ranges of chars listed in the class, escaped chars being decoded.
+    pub fn ranges(&self) -> Vec<(char, char)> {
+        self.items
+            .iter()
+            .map(|item| match &item.kind {
+                ClassItemKind::Single(c) => (c.value(), c.value()),
+                ClassItemKind::Range(start, end) => (start.value(), end.value()),
+            })
+            .collect()
+    }
+
+    /// Returns true if the class matches the character (the quantifier being ignored).
+    pub fn matches(&self, c: char) -> bool {
+        let found = self
+            .ranges()
+            .iter()
+            .any(|(start, end)| *start <= c && c <= *end);
+        found != self.negated
+    }
+
+    // `span` is the span of the whole class
+    fn errors(&self, span: &Span) -> Vec<ValidateError> {
+        let mut errors = vec![];
+        // a negated empty class matches any char
+        if self.items.is_empty() && !self.negated {
+            let span = span.clone();
+            let message = "character class is empty".to_string();
+            errors.push(ValidateError { span, message });
+        }
+        let ranges = self.ranges();
+        for (i, item) in self.items.iter().enumerate() {
+            for (c, span) in item.chars() {
+                if let ClassChar::Escape(escaped) = c {
+                    if !"nrt\\[]-".contains(escaped) {
+                        let message = format!("unknown escape sequence <{}>", c);
+                        errors.push(ValidateError { span, message });
+                    }
+                }
+            }
+            let (start, end) = ranges[i];
+            if start > end {
+                let span = item.span.clone();
+                let message = format!("range <{}> is reversed", item.kind);
+                errors.push(ValidateError { span, message });
+            } else if ranges[..i].iter().any(|(s, e)| *s <= end && start <= *e) {
+                let span = item.span.clone();
+                let message = format!("<{}> is already in the character class", item.kind);
+                errors.push(ValidateError { span, message });
+            }
+        }
+        errors
+    }
+}
+
+impl ClassItem {
+    // the chars of the item, with their spans
+    fn chars(&self) -> Vec<(ClassChar, Span)> {
+        let (start, end) = match &self.kind {
+            ClassItemKind::Single(c) => return vec![(*c, self.span.clone())],
+            ClassItemKind::Range(start, end) => (*start, *end),
+        };
+        let start_span = Span {
+            start: self.span.start,
+            end: self.span.start + start.len(),
+        };
+        let end_span = Span {
+            start: self.span.end - end.len(),
+            end: self.span.end,
+        };
+        vec![(start, start_span), (end, end_span)]
+    }
+}
+
+impl ClassChar {
+    /// Returns the char denoted, for instance a newline for `\n`.
+    pub fn value(&self) -> char {
+        match self {
+            ClassChar::Char(c) => *c,
+            ClassChar::Escape('n') => '\n',
+            ClassChar::Escape('r') => '\r',
+            ClassChar::Escape('t') => '\t',
+            ClassChar::Escape(c) => *c,
+        }
+    }
+
+    // number of chars as written
+    fn len(&self) -> usize {
+        match self {
+            ClassChar::Char(_) => 1,
+            ClassChar::Escape(_) => 2,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn item(start: usize, kind: ClassItemKind) -> ClassItem {
+        ClassItem {
+            span: Span {
+                start,
+                end: start + 1,
+            },
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        let class = CharacterClass {
+            negated: false,
+            items: vec![
+                item(
+                    1,
+                    ClassItemKind::Range(ClassChar::Char('0'), ClassChar::Char('9')),
+                ),
+                item(4, ClassItemKind::Single(ClassChar::Char('_'))),
+            ],
+            quantifier: None,
+        };
+        assert_eq!(class.ranges(), vec![('0', '9'), ('_', '_')]);
+        assert!(class.matches('5'));
+        assert!(class.matches('_'));
+        assert!(!class.matches('a'));
+
+        let class = CharacterClass {
+            negated: true,
+            items: vec![item(2, ClassItemKind::Single(ClassChar::Escape('n')))],
+            quantifier: None,
+        };
+        assert!(class.matches('a'));
+        assert!(!class.matches('\n'));
+    }
+
+    #[test]
+    fn test_character_class_errors() {
+        let g = parse("# General\na: [] ~[] [z-a] [a\\q-\\w] [a-za] [\\n\\]]\n").unwrap();
+        assert_eq!(
+            g.character_class_errors(),
+            vec![
+                ValidateError {
+                    span: Span { start: 13, end: 15 },
+                    message: "character class is empty".to_string(),
+                },
+                ValidateError {
+                    span: Span { start: 21, end: 24 },
+                    message: "range <z-a> is reversed".to_string(),
+                },
+                ValidateError {
+                    span: Span { start: 28, end: 30 },
+                    message: "unknown escape sequence <\\q>".to_string(),
+                },
+                ValidateError {
+                    span: Span { start: 31, end: 33 },
+                    message: "unknown escape sequence <\\w>".to_string(),
+                },
+                ValidateError {
+                    span: Span { start: 39, end: 40 },
+                    message: "<a> is already in the character class".to_string(),
+                },
+            ]
+        );
+    }
+}
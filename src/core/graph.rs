@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+/// Returns the strongly connected components of a directed graph (Tarjan's algorithm).
+///
+/// Components are returned in reverse topological order, each component keeping
+/// the order of `nodes`. Edges to nodes not in `nodes` are ignored.
+pub(crate) fn strongly_connected_components(
+    nodes: &[String],
+    edges: &HashMap<String, Vec<String>>,
+) -> Vec<Vec<String>> {
+    let mut tarjan = Tarjan {
+        edges,
+        index: HashMap::new(),
+        low_link: HashMap::new(),
+        stack: vec![],
+        components: vec![],
+    };
+    for node in nodes {
+        if !tarjan.index.contains_key(node) {
+            tarjan.visit(node, nodes);
+        }
+    }
+    tarjan
+        .components
+        .into_iter()
+        .map(|component| {
+            nodes
+                .iter()
+                .filter(|node| component.contains(node))
+                .cloned()
+                .collect()
+        })
+        .collect()
+}
+
+struct Tarjan<'a> {
+    edges: &'a HashMap<String, Vec<String>>,
+    index: HashMap<String, usize>,
+    low_link: HashMap<String, usize>,
+    stack: Vec<String>,
+    components: Vec<Vec<String>>,
+}
+
+impl Tarjan<'_> {
+    fn visit(&mut self, node: &str, nodes: &[String]) {
+        let index = self.index.len();
+        self.index.insert(node.to_string(), index);
+        self.low_link.insert(node.to_string(), index);
+        self.stack.push(node.to_string());
+
+        let successors = self.edges.get(node).cloned().unwrap_or_default();
+        for successor in successors.iter().filter(|s| nodes.contains(s)) {
+            if !self.index.contains_key(successor) {
+                self.visit(successor, nodes);
+                let low_link = self.low_link[node].min(self.low_link[successor]);
+                self.low_link.insert(node.to_string(), low_link);
+            } else if self.stack.contains(successor) {
+                let low_link = self.low_link[node].min(self.index[successor]);
+                self.low_link.insert(node.to_string(), low_link);
+            }
+        }
+
+        if self.low_link[node] == self.index[node] {
+            let mut component = vec![];
+            while let Some(member) = self.stack.pop() {
+                let done = member == node;
+                component.push(member);
+                if done {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strongly_connected_components() {
+        let nodes = ["a", "b", "c", "d"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>();
+        let mut edges = HashMap::new();
+        edges.insert("a".to_string(), vec!["b".to_string()]);
+        edges.insert("b".to_string(), vec!["c".to_string(), "x".to_string()]);
+        edges.insert("c".to_string(), vec!["b".to_string()]);
+        edges.insert("d".to_string(), vec!["d".to_string()]);
+        assert_eq!(
+            strongly_connected_components(&nodes, &edges),
+            vec![
+                vec!["b".to_string(), "c".to_string()],
+                vec!["a".to_string()],
+                vec!["d".to_string()],
+            ]
+        );
+    }
+}
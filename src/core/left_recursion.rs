@@ -0,0 +1,150 @@
+use super::graph::strongly_connected_components;
+use super::{Expression, ExpressionKind, Grammar, NonTerminal, ValidateError};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+impl Grammar {
+    /// Returns an error for each group of rules that are (directly or indirectly) left-recursive.
+    ///
+    /// The error is located on the reference that starts the cycle, and its message
+    /// lists the rules of the cycle.
+    pub(crate) fn left_recursion_errors(&self) -> Vec<ValidateError> {
+        let nullable = self.nullable_rules();
+        let mut ids: Vec<String> = vec![];
+        let mut left_non_terminals: HashMap<String, Vec<NonTerminal>> = HashMap::new();
+        for rule in self.get_rules() {
+            if !ids.contains(&rule.id) {
+                ids.push(rule.id.clone());
+            }
+            left_non_terminals
+                .entry(rule.id.clone())
+                .or_default()
+                .append(&mut rule.expression.left_non_terminals(&nullable));
+        }
+        let edges = left_non_terminals
+            .iter()
+            .map(|(id, non_terminals)| {
+                let successors = non_terminals.iter().map(|nt| nt.id.clone()).collect();
+                (id.clone(), successors)
+            })
+            .collect::<HashMap<String, Vec<String>>>();
+
+        let mut components = strongly_connected_components(&ids, &edges);
+        components.sort_by_key(|component| ids.iter().position(|id| *id == component[0]));
+
+        let mut errors = vec![];
+        for component in components {
+            let start = &component[0];
+            if let Some(cycle) = left_cycle(start, &component, &left_non_terminals) {
+                let mut path = vec![start.clone()];
+                path.extend(cycle.iter().map(|nt| nt.id.clone()));
+                let span = cycle[0].span.clone();
+                let message = format!("rule <{}> is left-recursive ({})", start, path.join(" -> "));
+                errors.push(ValidateError { span, message });
+            }
+        }
+        errors
+    }
+}
+
+// shortest chain of left references going from start back to itself,
+// staying inside the strongly connected component
+fn left_cycle(
+    start: &str,
+    component: &[String],
+    left_non_terminals: &HashMap<String, Vec<NonTerminal>>,
+) -> Option<Vec<NonTerminal>> {
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::new();
+    queue.push_back((start.to_string(), vec![]));
+    while let Some((id, path)) = queue.pop_front() {
+        for non_terminal in left_non_terminals.get(&id).into_iter().flatten() {
+            if !component.contains(&non_terminal.id) {
+                continue;
+            }
+            let mut path: Vec<NonTerminal> = path.clone();
+            path.push(non_terminal.clone());
+            if non_terminal.id == start {
+                return Some(path);
+            }
+            if visited.insert(non_terminal.id.clone()) {
+                queue.push_back((non_terminal.id.clone(), path));
+            }
+        }
+    }
+    None
+}
+
+impl Expression {
+    /// Returns the non-terminals that can be reached without consuming any input.
+    fn left_non_terminals(&self, nullable_rules: &HashSet<String>) -> Vec<NonTerminal> {
+        match &self.kind {
+            ExpressionKind::Choice(expressions) => expressions
+                .iter()
+                .flat_map(|e| e.left_non_terminals(nullable_rules))
+                .collect(),
+            ExpressionKind::Sequence(expressions) => {
+                let mut non_terminals = vec![];
+                for expression in expressions {
+                    non_terminals.append(&mut expression.left_non_terminals(nullable_rules));
+                    if !expression.is_nullable(nullable_rules) {
+                        break;
+                    }
+                }
+                non_terminals
+            }
+            // the negated expression is tried at the current position
+            ExpressionKind::Negate(expression)
+            | ExpressionKind::Quantifier(expression, _)
+            | ExpressionKind::Group(expression) => expression.left_non_terminals(nullable_rules),
+            ExpressionKind::Literal(_) | ExpressionKind::Regex(_) => vec![],
+            ExpressionKind::NonTerminal(id) => vec![NonTerminal {
+                span: self.span.clone(),
+                id: id.clone(),
+            }],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, Span, ValidateError};
+
+    #[test]
+    fn test_direct_left_recursion() {
+        // # General
+        // a: a "x" | "y"
+        let g = parse("# General\na: a \"x\" | \"y\"\n").unwrap();
+        assert_eq!(
+            g.left_recursion_errors(),
+            vec![ValidateError {
+                span: Span { start: 13, end: 14 },
+                message: "rule <a> is left-recursive (a -> a)".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_indirect_left_recursion() {
+        let g = parse(
+            "# General\n\
+             a: b? c\n\
+             b: \"x\"\n\
+             c: d* a \"y\" | \"z\"\n\
+             d: \"w\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            g.left_recursion_errors(),
+            vec![ValidateError {
+                span: Span { start: 16, end: 17 },
+                message: "rule <a> is left-recursive (a -> c -> a)".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_no_left_recursion() {
+        let g = parse("# General\na: \"x\" a | b a\nb: \"y\"+\n").unwrap();
+        assert!(g.left_recursion_errors().is_empty());
+    }
+}
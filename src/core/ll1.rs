@@ -0,0 +1,242 @@
+use super::{Analysis, CharacterClass, Expression, ExpressionKind, Grammar, Span, Terminal};
+use std::collections::BTreeSet;
+
+/// LL(1) conflict: the next terminal is not enough to decide how to continue.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    pub span: Span,
+    pub other_span: Option<Span>,
+    pub terminals: BTreeSet<Terminal>,
+    pub message: String,
+}
+
+impl Grammar {
+    /// Returns the LL(1) conflicts of the grammar.
+    ///
+    /// A choice is in conflict when two of its alternatives can start with the same char
+    /// (or with what follows the choice, for a nullable alternative).
+    /// A quantified expression is in conflict when its body can start with a char
+    /// that can also follow it.
+    pub fn ll1_conflicts(&self, start: &str) -> Vec<Conflict> {
+        let analysis = self.analyze(start);
+        let mut conflicts = vec![];
+        for rule in self.get_rules() {
+            let trailer = analysis.follow[&rule.id].clone();
+            analysis.add_conflicts(&rule.expression, &trailer, &mut conflicts);
+        }
+        conflicts
+    }
+}
+
+impl Analysis {
+    // terminals that can be seen when the expression is chosen
+    fn predict(&self, expression: &Expression, trailer: &BTreeSet<Terminal>) -> BTreeSet<Terminal> {
+        let mut terminals = self.first_of(expression);
+        if self.is_nullable(expression) {
+            terminals.extend(trailer.iter().cloned());
+        }
+        terminals
+    }
+
+    fn add_conflicts(
+        &self,
+        expression: &Expression,
+        trailer: &BTreeSet<Terminal>,
+        conflicts: &mut Vec<Conflict>,
+    ) {
+        match &expression.kind {
+            ExpressionKind::Choice(expressions) => {
+                for (i, expression) in expressions.iter().enumerate() {
+                    let predict = self.predict(expression, trailer);
+                    for other in &expressions[..i] {
+                        let terminals = self.overlapping(&predict, &self.predict(other, trailer));
+                        if !terminals.is_empty() {
+                            let message = format!(
+                                "choice is not LL(1): alternatives can both start with {}",
+                                text_terminals(&terminals)
+                            );
+                            conflicts.push(Conflict {
+                                span: expression.span.clone(),
+                                other_span: Some(other.span.clone()),
+                                terminals,
+                                message,
+                            });
+                        }
+                    }
+                }
+                for expression in expressions {
+                    self.add_conflicts(expression, trailer, conflicts);
+                }
+            }
+            ExpressionKind::Sequence(expressions) => {
+                let mut trailer = trailer.clone();
+                let mut trailers = vec![];
+                for expression in expressions.iter().rev() {
+                    trailers.push(trailer.clone());
+                    trailer = self.predict(expression, &trailer);
+                }
+                for (expression, trailer) in expressions.iter().zip(trailers.iter().rev()) {
+                    self.add_conflicts(expression, trailer, conflicts);
+                }
+            }
+            ExpressionKind::Group(expression) => self.add_conflicts(expression, trailer, conflicts),
+            // a fixed number of repetitions makes no choice
+            ExpressionKind::Quantifier(body, quantifier) => {
+                let first = self.first_of(body);
+                if quantifier.max() != Some(quantifier.min()) {
+                    self.add_quantifier_conflict(expression, &first, trailer, conflicts);
+                }
+                let mut trailer = trailer.clone();
+                if quantifier.max() != Some(1) {
+                    trailer.extend(first);
+                }
+                self.add_conflicts(body, &trailer, conflicts);
+            }
+            // a quantified class is repeated as well
+            ExpressionKind::Regex(CharacterClass {
+                quantifier: Some(quantifier),
+                ..
+            }) if quantifier.max() != Some(quantifier.min()) => {
+                let first = self.first_of(expression);
+                self.add_quantifier_conflict(expression, &first, trailer, conflicts);
+            }
+            ExpressionKind::Negate(_)
+            | ExpressionKind::Literal(_)
+            | ExpressionKind::Regex(_)
+            | ExpressionKind::NonTerminal(_) => {}
+        }
+    }
+
+    // `first` being the terminals that can start the repeated expression
+    fn add_quantifier_conflict(
+        &self,
+        expression: &Expression,
+        first: &BTreeSet<Terminal>,
+        trailer: &BTreeSet<Terminal>,
+        conflicts: &mut Vec<Conflict>,
+    ) {
+        let terminals = self.overlapping(first, trailer);
+        if !terminals.is_empty() {
+            let message = format!(
+                "quantifier is not LL(1): the repeated expression and what follows can both start with {}",
+                text_terminals(&terminals)
+            );
+            conflicts.push(Conflict {
+                span: expression.span.clone(),
+                other_span: None,
+                terminals,
+                message,
+            });
+        }
+    }
+}
+
+fn text_terminals(terminals: &BTreeSet<Terminal>) -> String {
+    terminals
+        .iter()
+        .map(|terminal| terminal.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_choice_conflict() {
+        // # General
+        // a: b "x" | "y" | b "z"
+        // b: "w"
+        let g = parse("# General\na: b \"x\" | \"y\" | b \"z\"\nb: \"w\"\n").unwrap();
+        assert_eq!(
+            g.ll1_conflicts("a"),
+            vec![Conflict {
+                span: Span { start: 27, end: 32 },
+                other_span: Some(Span { start: 13, end: 19 }),
+                terminals: BTreeSet::from([Terminal::Literal("w".to_string())]),
+                message: "choice is not LL(1): alternatives can both start with \"w\"".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_nullable_alternative_conflict() {
+        let g = parse("# General\na: (\"x\" | \"\") \"x\"\n").unwrap();
+        let conflicts = g.ll1_conflicts("a");
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(
+            conflicts[0].terminals,
+            BTreeSet::from([Terminal::Literal("x".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_quantifier_conflict() {
+        // # General
+        // a: "x"* "x"
+        let g = parse("# General\na: \"x\"* \"x\"\n").unwrap();
+        assert_eq!(
+            g.ll1_conflicts("a"),
+            vec![Conflict {
+                span: Span { start: 13, end: 17 },
+                other_span: None,
+                terminals: BTreeSet::from([Terminal::Literal("x".to_string())]),
+                message: "quantifier is not LL(1): the repeated expression and what follows can both start with \"x\"".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_overlapping_conflict() {
+        let terminals = |g: &str| {
+            parse(g)
+                .unwrap()
+                .ll1_conflicts("a")
+                .into_iter()
+                .map(|conflict| conflict.terminals)
+                .collect::<Vec<BTreeSet<Terminal>>>()
+        };
+        let literal = |value: &str| Terminal::Literal(value.to_string());
+        let regex = |value: &str| Terminal::Regex(value.to_string());
+        assert_eq!(
+            terminals("# General\na: [a-z] | [a-z]+\n"),
+            vec![BTreeSet::from([regex("[a-z]"), regex("[a-z]+")])]
+        );
+        assert_eq!(
+            terminals("# General\na: \"a\" | \"ab\"\n"),
+            vec![BTreeSet::from([literal("a"), literal("ab")])]
+        );
+        assert_eq!(
+            terminals("# General\na: [0-9]* \"5\"\n"),
+            vec![BTreeSet::from([regex("[0-9]*"), literal("5")])]
+        );
+        assert!(terminals("# General\na: (~\"\\n\")* \"\\n\"\n").is_empty());
+    }
+
+    #[test]
+    fn test_no_conflict() {
+        let g = parse("# General\na: (\"x\" b)* \"y\"\nb: \"z\" | \"\"\n").unwrap();
+        assert!(g.ll1_conflicts("a").is_empty());
+    }
+
+    #[test]
+    fn test_fixed_repeat_no_conflict() {
+        // a fixed number of repetitions makes no choice
+        for g in [
+            "# General\na: \"x\"{2} \"x\"\n",
+            "# General\na: \"x\"{2,2} \"x\"\n",
+            "# General\na: [0-9]{4} [0-9]\n",
+        ] {
+            assert!(parse(g).unwrap().ll1_conflicts("a").is_empty(), "{}", g);
+        }
+        assert_eq!(
+            parse("# General\na: \"x\"{2,3} \"x\"\n")
+                .unwrap()
+                .ll1_conflicts("a")
+                .len(),
+            1
+        );
+    }
+}
@@ -1,7 +1,17 @@
+mod analysis;
 mod ast;
+mod character_class;
+mod graph;
+mod left_recursion;
+mod ll1;
 mod non_terminal;
+mod nullable;
+mod productivity;
+mod reachability;
 mod validate;
 
+pub use analysis::*;
 pub use ast::*;
+pub use ll1::*;
 pub use non_terminal::*;
 pub use validate::*;
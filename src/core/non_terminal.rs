@@ -3,6 +3,7 @@ use super::{Expression, ExpressionKind, Rule, Span};
 use std::collections::HashMap;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NonTerminal {
     pub span: Span,
     pub id: String,
@@ -95,6 +96,8 @@ mod tests {
                     span: Span { start: 10, end: 14 },
                     kind: ExpressionKind::NonTerminal("name".to_string()),
                 },
+                examples: vec![],
+                doc: vec![],
             }
             .non_terminals(),
             vec![NonTerminal {
@@ -113,7 +116,9 @@ mod tests {
                 comment: Comment {
                     span: Span { start: 0, end: 5 },
                     value: "".to_string(),
+                    level: 1,
                 },
+                prose: None,
                 rules: vec![
                     Rule {
                         span: Span { start: 0, end: 5 },
@@ -131,6 +136,8 @@ mod tests {
                                 },
                             ]),
                         },
+                        examples: vec![],
+                        doc: vec![],
                     },
                     Rule {
                         span: Span { start: 13, end: 23 },
@@ -148,9 +155,12 @@ mod tests {
                                 },
                             ]),
                         },
+                        examples: vec![],
+                        doc: vec![],
                     },
                 ],
             }],
+            includes: vec![],
         };
         let mut map = HashMap::new();
         map.insert("a".to_string(), vec!["rule1".to_string(), "a".to_string()]);
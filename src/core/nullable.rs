@@ -0,0 +1,139 @@
+use super::{Expression, ExpressionKind, Grammar, ValidateError};
+use std::collections::HashSet;
+
+impl Grammar {
+    /// Returns the ids of the rules that can match the empty string.
+    pub fn nullable_rules(&self) -> HashSet<String> {
+        let rules = self.get_rules();
+        let mut nullable = HashSet::new();
+        loop {
+            let mut changed = false;
+            for rule in &rules {
+                if !nullable.contains(&rule.id) && rule.expression.is_nullable(&nullable) {
+                    nullable.insert(rule.id.clone());
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        nullable
+    }
+
+    /// Returns an error for each expression repeated without bound (`*`, `+` or `{m,}`)
+    /// that can match the empty string.
+    pub(crate) fn nullable_quantifier_errors(&self) -> Vec<ValidateError> {
+        let nullable = self.nullable_rules();
+        self.get_rules()
+            .iter()
+            .flat_map(|rule| rule.expression.nullable_quantifier_errors(&nullable))
+            .collect()
+    }
+}
+
+impl Expression {
+    /// Returns true if the expression can match the empty string,
+    /// given the set of nullable rules.
+    pub fn is_nullable(&self, nullable_rules: &HashSet<String>) -> bool {
+        match &self.kind {
+            ExpressionKind::Choice(expressions) => {
+                expressions.iter().any(|e| e.is_nullable(nullable_rules))
+            }
+            ExpressionKind::Sequence(expressions) => {
+                expressions.iter().all(|e| e.is_nullable(nullable_rules))
+            }
+            ExpressionKind::Group(expression) => expression.is_nullable(nullable_rules),
+            ExpressionKind::Negate(_) => false,
+            ExpressionKind::Quantifier(expression, quantifier) => {
+                quantifier.min() == 0 || expression.is_nullable(nullable_rules)
+            }
+            ExpressionKind::Literal(value) => value.is_empty(),
+            ExpressionKind::Regex(class) => {
+                matches!(&class.quantifier, Some(quantifier) if quantifier.min() == 0)
+            }
+            ExpressionKind::NonTerminal(id) => nullable_rules.contains(id),
+        }
+    }
+
+    fn nullable_quantifier_errors(&self, nullable_rules: &HashSet<String>) -> Vec<ValidateError> {
+        match &self.kind {
+            ExpressionKind::Choice(expressions) | ExpressionKind::Sequence(expressions) => {
+                expressions
+                    .iter()
+                    .flat_map(|e| e.nullable_quantifier_errors(nullable_rules))
+                    .collect()
+            }
+            ExpressionKind::Group(expression) | ExpressionKind::Negate(expression) => {
+                expression.nullable_quantifier_errors(nullable_rules)
+            }
+            ExpressionKind::Quantifier(expression, quantifier) => {
+                let mut errors = vec![];
+                if quantifier.max().is_none() && expression.is_nullable(nullable_rules) {
+                    let span = self.span.clone();
+                    let message = format!(
+                        "expression repeated by <{}> can match the empty string",
+                        quantifier
+                    );
+                    errors.push(ValidateError { span, message });
+                }
+                errors.append(&mut expression.nullable_quantifier_errors(nullable_rules));
+                errors
+            }
+            ExpressionKind::Literal(_)
+            | ExpressionKind::Regex(_)
+            | ExpressionKind::NonTerminal(_) => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, Span, ValidateError};
+
+    #[test]
+    fn test_nullable_rules() {
+        let g = parse(
+            "# General\n\
+             a: b c\n\
+             b: \"x\"?\n\
+             c: [a-z]* | d\n\
+             d: \"\"\n\
+             e: d \"y\"\n\
+             f: (b ~\"z\")+\n",
+        )
+        .unwrap();
+        let mut nullable = g.nullable_rules().into_iter().collect::<Vec<String>>();
+        nullable.sort();
+        assert_eq!(nullable, vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_nullable_quantifier_errors() {
+        let g = parse(
+            "# General\n\
+             a: (b?)* (\"\" | \"x\")+ b?\n\
+             b: c\n\
+             c: [a-z]* | (\"y\"*)?\n\
+             d: b{2} b{1,}\n",
+        )
+        .unwrap();
+        assert_eq!(
+            g.nullable_quantifier_errors(),
+            vec![
+                ValidateError {
+                    span: Span { start: 13, end: 18 },
+                    message: "expression repeated by <*> can match the empty string".to_string(),
+                },
+                ValidateError {
+                    span: Span { start: 19, end: 30 },
+                    message: "expression repeated by <+> can match the empty string".to_string(),
+                },
+                ValidateError {
+                    span: Span { start: 67, end: 72 },
+                    message: "expression repeated by <{1,}> can match the empty string".to_string(),
+                },
+            ]
+        );
+    }
+}
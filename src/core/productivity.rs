@@ -0,0 +1,140 @@
+use super::graph::strongly_connected_components;
+use super::{Expression, ExpressionKind, Grammar, ValidateError};
+use std::collections::{HashMap, HashSet};
+
+impl Grammar {
+    /// Returns the ids of the rules that can derive a finite string.
+    ///
+    /// References to undefined rules are considered productive, they are reported separately.
+    pub fn productive_rules(&self) -> HashSet<String> {
+        let rules = self.get_rules();
+        let defined = rules
+            .iter()
+            .map(|rule| rule.id.clone())
+            .collect::<HashSet<String>>();
+        let mut productive = HashSet::new();
+        loop {
+            let mut changed = false;
+            for rule in &rules {
+                if !productive.contains(&rule.id)
+                    && rule.expression.is_productive(&productive, &defined)
+                {
+                    productive.insert(rule.id.clone());
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        productive
+    }
+
+    /// Returns an error for each unproductive rule, mutually recursive rules being grouped.
+    pub(crate) fn unproductive_errors(&self) -> Vec<ValidateError> {
+        let productive = self.productive_rules();
+        let mut ids: Vec<String> = vec![];
+        let mut edges: HashMap<String, Vec<String>> = HashMap::new();
+        for rule in self.get_rules() {
+            if productive.contains(&rule.id) {
+                continue;
+            }
+            if !ids.contains(&rule.id) {
+                ids.push(rule.id.clone());
+            }
+            edges
+                .entry(rule.id.clone())
+                .or_default()
+                .extend(rule.non_terminals().into_iter().map(|nt| nt.id));
+        }
+
+        let mut components = strongly_connected_components(&ids, &edges);
+        components.sort_by_key(|component| ids.iter().position(|id| *id == component[0]));
+
+        let rules = self.get_rules();
+        let mut errors = vec![];
+        for component in components {
+            let rule = rules.iter().find(|rule| rule.id == component[0]).unwrap();
+            let message = if component.len() == 1 {
+                format!("rule <{}> can not derive a finite string", rule.id)
+            } else {
+                let ids = component
+                    .iter()
+                    .map(|id| format!("<{}>", id))
+                    .collect::<Vec<String>>();
+                format!("rules {} can not derive a finite string", ids.join(", "))
+            };
+            errors.push(ValidateError {
+                span: rule.span.clone(),
+                message,
+            });
+        }
+        errors
+    }
+}
+
+impl Expression {
+    fn is_productive(&self, productive_rules: &HashSet<String>, defined: &HashSet<String>) -> bool {
+        match &self.kind {
+            ExpressionKind::Choice(expressions) => expressions
+                .iter()
+                .any(|e| e.is_productive(productive_rules, defined)),
+            ExpressionKind::Sequence(expressions) => expressions
+                .iter()
+                .all(|e| e.is_productive(productive_rules, defined)),
+            ExpressionKind::Group(expression) => {
+                expression.is_productive(productive_rules, defined)
+            }
+            ExpressionKind::Quantifier(expression, quantifier) => {
+                quantifier.min() == 0 || expression.is_productive(productive_rules, defined)
+            }
+            ExpressionKind::Negate(_) | ExpressionKind::Literal(_) | ExpressionKind::Regex(_) => {
+                true
+            }
+            ExpressionKind::NonTerminal(id) => {
+                productive_rules.contains(id) || !defined.contains(id)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, Span, ValidateError};
+
+    #[test]
+    fn test_productive_rules() {
+        let g = parse("# General\na: \"x\" a | b\nb: c+ undefined\nc: \"y\" c\n").unwrap();
+        assert!(g.productive_rules().is_empty());
+
+        let g = parse("# General\na: \"x\" a?\nb: a* c\nc: \"y\"\n").unwrap();
+        let mut productive = g.productive_rules().into_iter().collect::<Vec<String>>();
+        productive.sort();
+        assert_eq!(productive, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_unproductive_errors() {
+        let g = parse(
+            "# General\n\
+             a: b | \"x\"\n\
+             b: \"y\" c\n\
+             c: \"z\" b\n\
+             d: \"w\" d\n",
+        )
+        .unwrap();
+        assert_eq!(
+            g.unproductive_errors(),
+            vec![
+                ValidateError {
+                    span: Span { start: 21, end: 29 },
+                    message: "rules <b>, <c> can not derive a finite string".to_string(),
+                },
+                ValidateError {
+                    span: Span { start: 39, end: 47 },
+                    message: "rule <d> can not derive a finite string".to_string(),
+                },
+            ]
+        );
+    }
+}
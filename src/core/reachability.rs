@@ -0,0 +1,121 @@
+use super::{Grammar, ValidateError};
+use std::collections::{HashMap, HashSet};
+
+impl Grammar {
+    /// Returns the ids of the rules that can be reached from the start rule.
+    pub fn reachable_rules(&self, start: &str) -> HashSet<String> {
+        let rules = self.get_rules();
+        let mut reachable = HashSet::new();
+        let mut stack = vec![start.to_string()];
+        while let Some(id) = stack.pop() {
+            if !reachable.insert(id.clone()) {
+                continue;
+            }
+            for rule in rules.iter().filter(|rule| rule.id == id) {
+                for non_terminal in rule.non_terminals() {
+                    if !reachable.contains(&non_terminal.id) {
+                        stack.push(non_terminal.id);
+                    }
+                }
+            }
+        }
+        reachable
+    }
+
+    /// Returns an error for each ruleset containing rules that can not be reached from the start rule.
+    ///
+    /// Rules that are not used at all are already reported as unused, and are not repeated here.
+    pub(crate) fn unreachable_errors(
+        &self,
+        start: &str,
+        non_terminals: &HashMap<String, Vec<String>>,
+    ) -> Vec<ValidateError> {
+        let reachable = self.reachable_rules(start);
+        let mut errors = vec![];
+        for ruleset in &self.rulesets {
+            let mut unreachable = vec![];
+            for rule in &ruleset.rules {
+                if reachable.contains(&rule.id) || !non_terminals.contains_key(&rule.id) {
+                    continue;
+                }
+                if !unreachable.iter().any(|(id, _)| *id == rule.id) {
+                    unreachable.push((rule.id.clone(), rule.span.clone()));
+                }
+            }
+            if let Some((_, span)) = unreachable.first() {
+                let ids = unreachable
+                    .iter()
+                    .map(|(id, _)| format!("<{}>", id))
+                    .collect::<Vec<String>>();
+                let message = if ids.len() == 1 {
+                    format!("rule {} is not reachable from <{}>", ids[0], start)
+                } else {
+                    format!(
+                        "rules {} are not reachable from <{}>",
+                        ids.join(", "),
+                        start
+                    )
+                };
+                errors.push(ValidateError {
+                    span: span.clone(),
+                    message,
+                });
+            }
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, Span, ValidateError};
+
+    #[test]
+    fn test_reachable_rules() {
+        let g = parse("# General\na: b\nb: \"x\" | b\nc: d\nd: c\n").unwrap();
+        let mut reachable = g.reachable_rules("a").into_iter().collect::<Vec<String>>();
+        reachable.sort();
+        assert_eq!(reachable, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_unreachable_errors() {
+        let g = parse(
+            "# General\n\
+             a: b\n\
+             b: \"x\"\n\
+             c: d | c\n\
+             # Other\n\
+             d: c\n\
+             e: e\n\
+             f: \"y\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            g.unreachable_errors("a", &g.non_terminals()),
+            vec![
+                ValidateError {
+                    span: Span { start: 22, end: 30 },
+                    message: "rule <c> is not reachable from <a>".to_string(),
+                },
+                ValidateError {
+                    span: Span { start: 39, end: 43 },
+                    message: "rules <d>, <e> are not reachable from <a>".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            g.unreachable_errors("d", &g.non_terminals()),
+            vec![
+                ValidateError {
+                    span: Span { start: 15, end: 21 },
+                    message: "rule <b> is not reachable from <d>".to_string(),
+                },
+                ValidateError {
+                    span: Span { start: 44, end: 48 },
+                    message: "rule <e> is not reachable from <d>".to_string(),
+                },
+            ]
+        );
+    }
+}
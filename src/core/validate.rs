@@ -10,10 +10,32 @@ pub struct ValidateError {
 
 impl Grammar {
     pub fn validate(&self) -> Vec<ValidateError> {
+        match self.get_rules().first() {
+            None => vec![],
+            Some(rule) => self.validate_from(&rule.id),
+        }
+    }
+
+    /// Validates the grammar, `start` being the start rule instead of the first one.
+    pub fn validate_from(&self, start: &str) -> Vec<ValidateError> {
+        self.validate_with(start, true)
+    }
+
+    /// Validates the grammar for a context-free parser such as the Earley recogniser:
+    /// left recursion and repeated nullable expressions are allowed.
+    pub fn validate_context_free(&self, start: &str) -> Vec<ValidateError> {
+        self.validate_with(start, false)
+    }
+
+    // `peg` adds the checks needed by the PEG interpreter
+    fn validate_with(&self, start: &str, peg: bool) -> Vec<ValidateError> {
         let mut errors = vec![];
 
         let non_terminals = self.non_terminals();
-        for rule in &self.get_rules()[1..] {
+        for rule in &self.get_rules() {
+            if rule.id == start {
+                continue;
+            }
             if let Some(error) = rule.unused_error(&non_terminals) {
                 errors.push(error);
             }
@@ -27,6 +49,15 @@ impl Grammar {
                 errors.push(error);
             }
         }
+        errors.append(&mut self.unreachable_errors(start, &non_terminals));
+        if peg {
+            errors.append(&mut self.left_recursion_errors());
+        }
+        errors.append(&mut self.unproductive_errors());
+        if peg {
+            errors.append(&mut self.nullable_quantifier_errors());
+        }
+        errors.append(&mut self.character_class_errors());
         errors
     }
 
@@ -71,7 +102,7 @@ impl Rule {
             .map(|rule| rule.id.clone())
             .collect::<Vec<String>>();
         for non_terminal in self.non_terminals() {
-            if !rules.iter().cloned().any(|x| x == non_terminal.id) {
+            if !rules.contains(&non_terminal.id) {
                 let span = non_terminal.span.clone();
                 let message = format!("rule <{}> is not defined", non_terminal.id);
                 let error = ValidateError { span, message };
@@ -106,6 +137,8 @@ mod tests {
                 span: Span { start: 15, end: 16 },
                 kind: ExpressionKind::NonTerminal("a".to_string()),
             },
+            examples: vec![],
+            doc: vec![],
         }
     }
 
@@ -126,6 +159,13 @@ mod tests {
         assert!(rule().unused_error(&terminals).is_none());
     }
 
+    #[test]
+    pub fn test_validate_context_free() {
+        let g = crate::parse("# General\nexpr: expr \"+\" term | term\nterm: (\"x\"?)*\n").unwrap();
+        assert_eq!(g.validate_from("expr").len(), 2);
+        assert!(g.validate_context_free("expr").is_empty());
+    }
+
     #[test]
     pub fn test_undefined_error() {
         let mut rules = vec![];
@@ -137,6 +177,8 @@ mod tests {
                 span: Span { start: 5, end: 10 },
                 kind: ExpressionKind::Literal("a".to_string()),
             },
+            examples: vec![],
+            doc: vec![],
         });
         assert_eq!(
             rule().undefined_error(&rules),
@@ -152,6 +194,8 @@ mod tests {
                 span: Span { start: 5, end: 10 },
                 kind: ExpressionKind::Literal("a".to_string()),
             },
+            examples: vec![],
+            doc: vec![],
         });
         //assert!(rule().undefined_error(&rules).is_empty());
     }
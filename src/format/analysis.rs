@@ -0,0 +1,58 @@
+use super::Grammar;
+use crate::Terminal;
+use std::collections::BTreeSet;
+
+/// Returns a text report of the nullable, FIRST and FOLLOW sets of every rule.
+pub fn format_analysis(g: &Grammar, start: &str) -> String {
+    let analysis = g.analyze(start);
+    let mut ids: Vec<String> = vec![];
+    for rule in g.get_rules() {
+        if !ids.contains(&rule.id) {
+            ids.push(rule.id);
+        }
+    }
+    let mut s = "".to_string();
+    for id in ids {
+        s.push_str(&format!("{}\n", id));
+        s.push_str(&format!(
+            "    nullable: {}\n",
+            analysis.nullable.contains(&id)
+        ));
+        s.push_str(&format!(
+            "    first: {}\n",
+            text_terminals(&analysis.first[&id])
+        ));
+        s.push_str(&format!(
+            "    follow: {}\n",
+            text_terminals(&analysis.follow[&id])
+        ));
+    }
+    s
+}
+
+fn text_terminals(terminals: &BTreeSet<Terminal>) -> String {
+    if terminals.is_empty() {
+        return "(none)".to_string();
+    }
+    terminals
+        .iter()
+        .map(|terminal| terminal.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_format_analysis() {
+        let g = parse("# General\na: b? \"x\"\nb: \"y\"\n").unwrap();
+        assert_eq!(
+            format_analysis(&g, "a"),
+            "a\n    nullable: false\n    first: \"x\" \"y\"\n    follow: <eof>\n\
+             b\n    nullable: false\n    first: \"y\"\n    follow: \"x\"\n"
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use super::Grammar;
+use crate::Coverage;
+
+/// Returns a text summary of the coverage: the rules and choice alternatives never used.
+///
+/// The alternatives of the rules never used are not listed.
+pub fn format_coverage(g: &Grammar, coverage: &Coverage) -> String {
+    let mut ids: Vec<String> = vec![];
+    for rule in g.get_rules() {
+        if !ids.contains(&rule.id) {
+            ids.push(rule.id);
+        }
+    }
+    let uncovered_rules = ids
+        .iter()
+        .filter(|id| !coverage.rules.contains(*id))
+        .collect::<Vec<&String>>();
+    let alternatives = g.alternatives();
+    let uncovered_alternatives = alternatives
+        .iter()
+        .filter(|(_, e)| !coverage.alternatives.contains(&e.span))
+        .collect::<Vec<_>>();
+
+    let mut s = format!(
+        "rules: {}/{} covered\n",
+        ids.len() - uncovered_rules.len(),
+        ids.len()
+    );
+    s.push_str(&format!(
+        "alternatives: {}/{} covered\n",
+        alternatives.len() - uncovered_alternatives.len(),
+        alternatives.len()
+    ));
+    if !uncovered_rules.is_empty() {
+        s.push_str("uncovered rules:\n");
+        for id in uncovered_rules {
+            s.push_str(&format!("    {}\n", id));
+        }
+    }
+    let uncovered_alternatives = uncovered_alternatives
+        .iter()
+        .filter(|(rule, _)| coverage.rules.contains(&rule.id))
+        .collect::<Vec<_>>();
+    if !uncovered_alternatives.is_empty() {
+        s.push_str("uncovered alternatives:\n");
+        for (rule, expression) in uncovered_alternatives {
+            s.push_str(&format!("    {}: {}\n", rule.id, expression.to_source()));
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_format_coverage() {
+        let g = parse("# General\na: b | \"y\" c\nb: \"x\" | \"z\"\nc: \"w\"\n").unwrap();
+        let mut coverage = Coverage::default();
+        g.cover("a", "x", &mut coverage).unwrap();
+        assert_eq!(
+            format_coverage(&g, &coverage),
+            "rules: 2/3 covered\n\
+             alternatives: 2/4 covered\n\
+             uncovered rules:\n    c\n\
+             uncovered alternatives:\n    a: \"y\" c\n    b: \"z\"\n"
+        );
+    }
+}
@@ -0,0 +1,43 @@
+use super::Grammar;
+use std::collections::BTreeMap;
+
+#[derive(serde::Serialize)]
+struct JsonGrammar<'a> {
+    grammar: &'a Grammar,
+    non_terminals: BTreeMap<String, Vec<String>>,
+}
+
+/// Serializes the grammar and its used-by map (see [`Grammar::non_terminals`]) to JSON.
+pub fn format_json(g: &Grammar) -> String {
+    let non_terminals = g.non_terminals().into_iter().collect();
+    let json = JsonGrammar {
+        grammar: g,
+        non_terminals,
+    };
+    serde_json::to_string_pretty(&json).expect("grammar can be serialized")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_format_json() {
+        let g = parse("# General\nrule1: a\na: \"A\"\n").unwrap();
+        let value: serde_json::Value = serde_json::from_str(&format_json(&g)).unwrap();
+        assert_eq!(
+            value["non_terminals"],
+            serde_json::json!({ "a": ["rule1"] })
+        );
+        assert_eq!(
+            value["grammar"]["rulesets"][0]["rules"][1]["expression"],
+            serde_json::json!({
+                "span": { "start": 22, "end": 25 },
+                "kind": { "Literal": "A" }
+            })
+        );
+        let grammar: Grammar = serde_json::from_value(value["grammar"].clone()).unwrap();
+        assert_eq!(grammar, g);
+    }
+}
@@ -0,0 +1,240 @@
+use super::{Expression, ExpressionKind, Grammar, Rule, RuleSet};
+use crate::Quantifier;
+use std::collections::HashMap;
+
+pub fn format_markdown(g: &Grammar) -> String {
+    let non_terminals = g.non_terminals();
+    g.to_markdown(&non_terminals)
+}
+
+impl Grammar {
+    pub fn to_markdown(&self, used_by: &HashMap<String, Vec<String>>) -> String {
+        self.rulesets
+            .iter()
+            .map(|ruleset| ruleset.to_markdown(used_by))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl RuleSet {
+    pub fn to_markdown(&self, used_by: &HashMap<String, Vec<String>>) -> String {
+        let mut s = format!(
+            "{} {}\n\n",
+            "#".repeat((self.comment.level + 1).min(6)),
+            encode_markdown(&self.comment.value)
+        );
+        if let Some(prose) = &self.prose {
+            s.push_str(&format!("{}\n\n", prose.value));
+        }
+        s.push_str("| Rule | Expression | Used by |\n");
+        s.push_str("|------|------------|---------|\n");
+        for rule in &self.rules {
+            let used_by = match used_by.get(&rule.id) {
+                Some(used_by) => used_by.clone(),
+                None => vec![],
+            };
+            s.push_str(format!("{}\n", rule.to_markdown(&used_by)).as_str());
+        }
+        s
+    }
+}
+
+impl Rule {
+    pub fn to_markdown(&self, used_by: &[String]) -> String {
+        let used_by = used_by
+            .iter()
+            .map(|id| markdown_non_terminal(id))
+            .collect::<Vec<String>>();
+        let mut expression = self.expression.to_markdown();
+        if !self.doc.is_empty() {
+            let doc = self
+                .doc
+                .iter()
+                .map(|doc| doc.value.trim())
+                .filter(|value| !value.is_empty())
+                .collect::<Vec<&str>>()
+                .join(" ");
+            expression.push_str(&format!(
+                "<br>{}",
+                encode_markdown(&doc).replace('|', r"\|")
+            ));
+        }
+        for example in &self.examples {
+            expression.push_str(&format!("<br>Example: {}", markdown_code(&example.value)));
+        }
+        format!(
+            r#"| <a id="{id}"></a>{id} | {} | {} |"#,
+            expression,
+            used_by.join(", "),
+            id = self.id
+        )
+    }
+}
+
+impl Expression {
+    pub fn to_markdown(&self) -> String {
+        self.kind.to_markdown()
+    }
+}
+
+impl ExpressionKind {
+    pub fn to_markdown(&self) -> String {
+        match self {
+            ExpressionKind::Choice(expressions) => expressions
+                .iter()
+                .map(|e| e.to_markdown())
+                .collect::<Vec<String>>()
+                .join(r" \| "),
+            ExpressionKind::Sequence(expressions) => expressions
+                .iter()
+                .map(|e| e.to_markdown())
+                .collect::<Vec<String>>()
+                .join(" "),
+            ExpressionKind::Group(expression) => format!("({})", expression.to_markdown()),
+            ExpressionKind::Negate(expression) => format!(r"\~{}", expression.to_markdown()),
+            ExpressionKind::Quantifier(expression, quantifier) => {
+                format!("{}{}", expression.to_markdown(), quantifier.to_markdown())
+            }
+            ExpressionKind::Literal(_) => markdown_code(&self.to_source()),
+            ExpressionKind::Regex(class) => markdown_code(&class.to_string()),
+            ExpressionKind::NonTerminal(s) => markdown_non_terminal(s),
+        }
+    }
+}
+
+impl Quantifier {
+    pub fn to_markdown(&self) -> String {
+        match self {
+            Quantifier::Many => r"\*".to_string(),
+            _ => self.to_string(),
+        }
+    }
+}
+
+fn markdown_non_terminal(s: &str) -> String {
+    format!("[{name}](#{name})", name = s)
+}
+
+// code span inside a table cell
+// the pipe must still be escaped and the delimiter must be longer
+// than any backtick run of the content
+fn markdown_code(s: &str) -> String {
+    let mut longest = 0;
+    let mut current = 0;
+    for c in s.chars() {
+        if c == '`' {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    let delimiter = "`".repeat(longest + 1);
+    let s = s.replace('|', r"\|");
+    if longest > 0 {
+        format!("{delimiter} {s} {delimiter}")
+    } else {
+        format!("{delimiter}{s}{delimiter}")
+    }
+}
+
+fn encode_markdown(s: &str) -> String {
+    s.replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Comment, Span};
+
+    #[test]
+    fn test_rule() {
+        let used_by = vec!["other".to_string()];
+        assert_eq!(
+            Rule {
+                span: Span { start: 0, end: 1 },
+                id: "name".to_string(),
+                expression: Expression {
+                    span: Span { start: 2, end: 3 },
+                    kind: ExpressionKind::NonTerminal("a".to_string()),
+                },
+                examples: vec![],
+                doc: vec![],
+            }
+            .to_markdown(&used_by),
+            r#"| <a id="name"></a>name | [a](#a) | [other](#other) |"#,
+        );
+    }
+
+    #[test]
+    fn test_ruleset() {
+        let ruleset = RuleSet {
+            comment: Comment {
+                span: Span { start: 0, end: 9 },
+                value: "General".to_string(),
+                level: 1,
+            },
+            prose: None,
+            rules: vec![Rule {
+                span: Span { start: 10, end: 18 },
+                id: "name".to_string(),
+                expression: Expression {
+                    span: Span { start: 16, end: 18 },
+                    kind: ExpressionKind::Literal("a".to_string()),
+                },
+                examples: vec![],
+                doc: vec![],
+            }],
+        };
+        assert_eq!(
+            ruleset.to_markdown(&HashMap::new()),
+            "## General\n\n\
+             | Rule | Expression | Used by |\n\
+             |------|------------|---------|\n\
+             | <a id=\"name\"></a>name | `\"a\"` |  |\n"
+        );
+    }
+
+    #[test]
+    fn test_choice() {
+        assert_eq!(
+            ExpressionKind::Choice(vec![
+                Expression {
+                    span: Span { start: 0, end: 1 },
+                    kind: ExpressionKind::NonTerminal("a".to_string()),
+                },
+                Expression {
+                    span: Span { start: 2, end: 5 },
+                    kind: ExpressionKind::Literal("|".to_string()),
+                },
+            ])
+            .to_markdown(),
+            r#"[a](#a) \| `"\|"`"#,
+        );
+    }
+
+    #[test]
+    fn test_quantifier() {
+        assert_eq!(
+            ExpressionKind::Quantifier(
+                Box::new(Expression {
+                    span: Span { start: 0, end: 3 },
+                    kind: ExpressionKind::Negate(Box::new(Expression {
+                        span: Span { start: 1, end: 2 },
+                        kind: ExpressionKind::NonTerminal("a".to_string()),
+                    })),
+                }),
+                Quantifier::Many
+            )
+            .to_markdown(),
+            r"\~[a](#a)\*",
+        );
+    }
+
+    #[test]
+    fn test_code() {
+        assert_eq!(markdown_code("[a-z]"), "`[a-z]`");
+        assert_eq!(markdown_code("\"`\""), "`` \"`\" ``");
+    }
+}
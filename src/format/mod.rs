@@ -1,10 +1,49 @@
-use super::{Comment, Expression, ExpressionKind, Grammar, Rule, RuleSet};
-use crate::Quantifier;
-use std::collections::HashMap;
-
-pub fn format_html(g: &Grammar, s: &str, section_header: &str, section_id: bool) -> String {
+use super::{Comment, DocComment, Example, Expression, ExpressionKind, Grammar, Rule, RuleSet};
+use crate::{Coverage, Quantifier};
+use std::collections::{HashMap, HashSet};
+
+mod analysis;
+mod coverage;
+#[cfg(feature = "serde")]
+mod json;
+mod markdown;
+mod prose;
+mod railroad;
+mod source;
+mod tree;
+
+pub use analysis::*;
+pub use coverage::*;
+#[cfg(feature = "serde")]
+pub use json::*;
+pub use markdown::*;
+pub use railroad::*;
+pub use source::*;
+
+/// With a table of contents, the sections always have an id.
+pub fn format_html(
+    g: &Grammar,
+    s: &str,
+    section_header: &str,
+    section_id: bool,
+    railroad: bool,
+    coverage: Option<&Coverage>,
+    toc: bool,
+) -> String {
     let non_terminals = g.non_terminals();
-    g.to_html(s, &non_terminals, section_header, section_id)
+    let html = g.to_html(
+        s,
+        &non_terminals,
+        section_header,
+        section_id || toc,
+        railroad,
+        coverage,
+    );
+    if toc {
+        format!("{}{}", g.toc_to_html(), html)
+    } else {
+        html
+    }
 }
 
 impl Grammar {
@@ -14,17 +53,86 @@ impl Grammar {
         used_by: &HashMap<String, Vec<String>>,
         section_header: &str,
         section_id: bool,
+        railroad: bool,
+        coverage: Option<&Coverage>,
     ) -> String {
         let mut s = "".to_string();
-        for ruleset in &self.rulesets {
+        let section_ids = self.section_ids();
+        // levels of the sections not closed yet
+        let mut levels: Vec<usize> = vec![];
+        for (ruleset, id) in self.rulesets.iter().zip(&section_ids) {
+            while levels
+                .last()
+                .is_some_and(|level| *level >= ruleset.comment.level)
+            {
+                s.push_str("</div>");
+                levels.pop();
+            }
             s.push_str(
                 ruleset
-                    .to_html(input, used_by, section_header, section_id)
+                    .open_html(
+                        input,
+                        used_by,
+                        section_header,
+                        section_id.then_some(id.as_str()),
+                        railroad,
+                        coverage,
+                    )
                     .as_str(),
             );
+            levels.push(ruleset.comment.level);
+        }
+        s.push_str(&"</div>".repeat(levels.len()));
+        s
+    }
+
+    /// Returns the list of the sections, nested as in the grammar, linking to their ids.
+    pub fn toc_to_html(&self) -> String {
+        let mut s = r#"<nav class="grammar-toc">"#.to_string();
+        // levels of the lists not closed yet
+        let mut levels: Vec<usize> = vec![];
+        for (ruleset, id) in self.rulesets.iter().zip(self.section_ids()) {
+            let level = ruleset.comment.level;
+            while levels.last().is_some_and(|other| *other > level) {
+                s.push_str("</li></ul>");
+                levels.pop();
+            }
+            if levels.last() == Some(&level) {
+                s.push_str("</li>");
+            } else {
+                s.push_str("<ul>");
+                levels.push(level);
+            }
+            s.push_str(
+                format!(
+                    r##"<li><a href="#{}">{}</a>"##,
+                    encode_html(&id),
+                    ruleset.comment.to_html()
+                )
+                .as_str(),
+            );
         }
+        s.push_str(&"</li></ul>".repeat(levels.len()));
+        s.push_str("</nav>");
         s
     }
+
+    // ids of the sections, an id already used being followed by a number (`lexical-2`, `lexical-3`...)
+    fn section_ids(&self) -> Vec<String> {
+        let mut used = HashSet::new();
+        let mut ids = vec![];
+        for ruleset in &self.rulesets {
+            let id = comment_to_id(&ruleset.comment.value);
+            let mut unique = id.clone();
+            let mut n = 2;
+            while !used.insert(unique.clone()) {
+                unique = format!("{}-{}", id, n);
+                n += 1;
+            }
+            ids.push(unique);
+        }
+        ids
+    }
 }
 
 impl RuleSet {
@@ -34,15 +142,37 @@ impl RuleSet {
         used_by: &HashMap<String, Vec<String>>,
         section_header: &str,
         section_id: bool,
+        railroad: bool,
+        coverage: Option<&Coverage>,
+    ) -> String {
+        let id = comment_to_id(&self.comment.value);
+        let mut s = self.open_html(
+            input,
+            used_by,
+            section_header,
+            section_id.then_some(id.as_str()),
+            railroad,
+            coverage,
+        );
+        s.push_str("</div>");
+        s
+    }
+
+    // the section without its closing tag, so that the subsections can be nested inside
+    fn open_html(
+        &self,
+        input: &str,
+        used_by: &HashMap<String, Vec<String>>,
+        section_header: &str,
+        section_id: Option<&str>,
+        railroad: bool,
+        coverage: Option<&Coverage>,
     ) -> String {
         let mut s = r#"<div class="grammar-ruleset">"#.to_string();
-        let section_id = if section_id {
-            format!(
-                " id=\"{}\"",
-                encode_html(&comment_to_id(&self.comment.value))
-            )
-        } else {
-            "".to_string()
+        let section_header = heading_tag(section_header, self.comment.level);
+        let section_id = match section_id {
+            Some(id) => format!(" id=\"{}\"", encode_html(id)),
+            None => "".to_string(),
         };
         s.push_str(
             format!(
@@ -51,19 +181,33 @@ impl RuleSet {
             )
             .as_str(),
         );
+        if let Some(prose) = &self.prose {
+            s.push_str(prose.to_html().as_str());
+        }
 
         for rule in &self.rules {
             let used_by = match used_by.get(&rule.id) {
                 Some(used_by) => used_by.clone(),
                 None => vec![],
             };
-            s.push_str(format!("{}\n", rule.to_html(input, &used_by)).as_str());
+            s.push_str(format!("{}\n", rule.to_html(input, &used_by, railroad, coverage)).as_str());
         }
-        s.push_str("</div>");
         s
     }
 }
 
+// the header of a subsection is one level below the header of its section (`h2` then `h3`...),
+// unless the section header is not a heading
+fn heading_tag(section_header: &str, level: usize) -> String {
+    match section_header
+        .strip_prefix('h')
+        .and_then(|n| n.parse::<usize>().ok())
+    {
+        Some(n @ 1..=6) => format!("h{}", (n + level - 1).min(6)),
+        _ => section_header.to_string(),
+    }
+}
+
 impl Comment {
     pub fn to_html(&self) -> String {
         encode_html(&self.value)
@@ -80,10 +224,32 @@ fn comment_to_id(value: &str) -> String {
 }
 
 impl Rule {
-    pub fn to_html(&self, input: &str, used_by: &[String]) -> String {
-        let mut s = r#"<div class="grammar-rule">"#.to_string();
+    /// With a coverage, the rules and choice alternatives never used are shaded.
+    pub fn to_html(
+        &self,
+        input: &str,
+        used_by: &[String],
+        railroad: bool,
+        coverage: Option<&Coverage>,
+    ) -> String {
+        let (mut s, coverage) = match coverage {
+            Some(coverage) if !coverage.rules.contains(&self.id) => (
+                r#"<div class="grammar-rule grammar-uncovered">"#.to_string(),
+                None,
+            ),
+            _ => (r#"<div class="grammar-rule">"#.to_string(), coverage),
+        };
         s.push_str(html_rule_declaration(&self.id, used_by).as_str());
-        s.push_str(html_rule_expression(&self.expression, input).as_str());
+        if !self.doc.is_empty() {
+            s.push_str(html_rule_doc(&self.doc).as_str());
+        }
+        s.push_str(html_rule_expression(&self.expression, input, coverage).as_str());
+        if !self.examples.is_empty() {
+            s.push_str(html_rule_examples(&self.examples).as_str());
+        }
+        if railroad {
+            s.push_str(html_rule_railroad(self).as_str());
+        }
         s.push_str("</div>");
         s
     }
@@ -116,49 +282,117 @@ fn html_used_by(used_by: &[String]) -> String {
     )
 }
 
-fn html_rule_expression(expr: &Expression, input: &str) -> String {
+fn html_rule_expression(expr: &Expression, input: &str, coverage: Option<&Coverage>) -> String {
     let mut s = r#"<div class="grammar-rule-expression">"#.to_string();
-    s.push_str(expr.to_html(0, input).as_str());
+    s.push_str(expr.to_html_with_coverage(0, input, coverage).as_str());
+    s.push_str("</div>");
+    s
+}
+
+// an empty doc comment line separates two paragraphs
+fn html_rule_doc(doc: &[DocComment]) -> String {
+    let lines = doc
+        .iter()
+        .map(|doc| encode_html(doc.value.trim()))
+        .collect::<Vec<String>>();
+    let mut s = r#"<div class="grammar-rule-doc">"#.to_string();
+    for paragraph in lines.split(|line| line.is_empty()) {
+        if !paragraph.is_empty() {
+            s.push_str(format!("<p>{}</p>", paragraph.join(" ")).as_str());
+        }
+    }
     s.push_str("</div>");
     s
 }
 
+fn html_rule_examples(examples: &[Example]) -> String {
+    let mut s = r#"<div class="grammar-rule-examples">"#.to_string();
+    for example in examples {
+        s.push_str(
+            format!(
+                r#"<code class="grammar-rule-example">{}</code>"#,
+                encode_html(&example.value)
+            )
+            .as_str(),
+        );
+    }
+    s.push_str("</div>");
+    s
+}
+
+fn html_rule_railroad(rule: &Rule) -> String {
+    format!(
+        r#"<div class="grammar-rule-railroad">{}</div>"#,
+        rule.to_svg(false)
+    )
+}
+
 impl Expression {
     pub fn to_html(&self, indent: usize, input: &str) -> String {
         self.kind.to_html(indent, input)
     }
+
+    pub fn to_html_with_coverage(
+        &self,
+        indent: usize,
+        input: &str,
+        coverage: Option<&Coverage>,
+    ) -> String {
+        self.kind.to_html_with_coverage(indent, input, coverage)
+    }
 }
 
 impl ExpressionKind {
     pub fn to_html(&self, indent: usize, input: &str) -> String {
+        self.to_html_with_coverage(indent, input, None)
+    }
+
+    /// With a coverage, the choice alternatives never used are shaded.
+    pub fn to_html_with_coverage(
+        &self,
+        indent: usize,
+        input: &str,
+        coverage: Option<&Coverage>,
+    ) -> String {
         match self {
-            ExpressionKind::Choice(expressions) => html_choice(expressions.clone(), indent, input),
+            ExpressionKind::Choice(expressions) => {
+                html_choice(expressions.clone(), indent, input, coverage)
+            }
             ExpressionKind::Sequence(expressions) => {
-                html_sequence(expressions.clone(), indent, input)
+                html_sequence(expressions.clone(), indent, input, coverage)
+            }
+            ExpressionKind::Group(expression) => {
+                html_group(*expression.clone(), indent, input, coverage)
+            }
+            ExpressionKind::Negate(expression) => {
+                html_negate(*expression.clone(), indent, input, coverage)
             }
-            ExpressionKind::Group(expression) => html_group(*expression.clone(), indent, input),
-            ExpressionKind::Negate(expression) => html_negate(*expression.clone(), indent, input),
             ExpressionKind::Quantifier(expression, quantifier) => {
-                html_quantifier(*expression.clone(), quantifier, indent, input)
+                html_quantifier(*expression.clone(), quantifier, indent, input, coverage)
             }
-            ExpressionKind::Literal(s) => html_literal(s),
-            ExpressionKind::Regex(s) => html_regex(s),
+            ExpressionKind::Literal(s) => html_literal(&source::escape_literal(s)),
+            ExpressionKind::Regex(class) => html_regex(&class.to_string()),
             ExpressionKind::NonTerminal(s) => html_non_terminal(s),
         }
     }
 }
 
-fn html_choice(expressions: Vec<Expression>, indent: usize, input: &str) -> String {
+fn html_choice(
+    expressions: Vec<Expression>,
+    indent: usize,
+    input: &str,
+    coverage: Option<&Coverage>,
+) -> String {
     let mut expressions = expressions;
     let first_expression = expressions.remove(0);
-    let mut s = first_expression.to_html(indent + 1, input);
+    let mut s = html_alternative(&first_expression, indent + 1, input, coverage);
     let mut previous = first_expression.span.end;
     for expression in expressions {
         if indent == 0 && input[previous..expression.span.start].contains('\n') {
             s.push_str("<br>\n");
         }
         s.push_str(r#"<span class="grammar-symbol">|</span>"#);
-        s.push_str(expression.to_html(indent + 1, input).as_str());
+        s.push_str(html_alternative(&expression, indent + 1, input, coverage).as_str());
         previous = expression.span.end;
     }
     if s.contains("<br>") {
@@ -167,10 +401,30 @@ fn html_choice(expressions: Vec<Expression>, indent: usize, input: &str) -> Stri
     s
 }
 
-fn html_sequence(expressions: Vec<Expression>, indent: usize, input: &str) -> String {
+fn html_alternative(
+    expression: &Expression,
+    indent: usize,
+    input: &str,
+    coverage: Option<&Coverage>,
+) -> String {
+    match coverage {
+        Some(coverage) if !coverage.alternatives.contains(&expression.span) => format!(
+            r#"<span class="grammar-uncovered">{}</span>"#,
+            expression.to_html(indent, input)
+        ),
+        _ => expression.to_html_with_coverage(indent, input, coverage),
+    }
+}
+
+fn html_sequence(
+    expressions: Vec<Expression>,
+    indent: usize,
+    input: &str,
+    coverage: Option<&Coverage>,
+) -> String {
     let mut expressions = expressions;
     let first_expression = expressions.remove(0);
-    let mut s = first_expression.to_html(indent + 1, input);
+    let mut s = first_expression.to_html_with_coverage(indent + 1, input, coverage);
     let mut previous = first_expression.span.end;
     for expression in expressions {
         if input[previous..expression.span.start].contains('\n') {
@@ -178,23 +432,37 @@ fn html_sequence(expressions: Vec<Expression>, indent: usize, input: &str) -> St
         } else {
             s.push_str("&nbsp;");
         }
-        s.push_str(expression.to_html(indent + 1, input).as_str());
+        s.push_str(
+            expression
+                .to_html_with_coverage(indent + 1, input, coverage)
+                .as_str(),
+        );
         previous = expression.span.end;
     }
     s
 }
 
-fn html_group(expression: Expression, indent: usize, input: &str) -> String {
+fn html_group(
+    expression: Expression,
+    indent: usize,
+    input: &str,
+    coverage: Option<&Coverage>,
+) -> String {
     format!(
         r#"<span class="grammar-symbol">(</span>{}<span class="grammar-symbol">)</span>"#,
-        expression.to_html(indent, input)
+        expression.to_html_with_coverage(indent, input, coverage)
     )
 }
 
-fn html_negate(expression: Expression, indent: usize, input: &str) -> String {
+fn html_negate(
+    expression: Expression,
+    indent: usize,
+    input: &str,
+    coverage: Option<&Coverage>,
+) -> String {
     format!(
         r#"<span class="grammar-symbol">~</span>{}"#,
-        expression.to_html(indent, input)
+        expression.to_html_with_coverage(indent, input, coverage)
     )
 }
 
@@ -203,10 +471,11 @@ fn html_quantifier(
     quantifier: &Quantifier,
     indent: usize,
     input: &str,
+    coverage: Option<&Coverage>,
 ) -> String {
     format!(
         r#"{}{}"#,
-        expression.to_html(indent, input),
+        expression.to_html_with_coverage(indent, input, coverage),
         quantifier.to_html()
     )
 }
@@ -227,20 +496,24 @@ fn encode_html(s: &str) -> String {
     s.replace('>', "&gt;").replace('<', "&lt;")
 }
 
+// value of a double-quoted attribute
+fn encode_attribute(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 impl Quantifier {
     pub fn to_html(&self) -> String {
-        let s = match self {
-            Quantifier::ZeroOrOne => "?",
-            Quantifier::OneOrMany => "+",
-            Quantifier::Many => "*",
-        };
-        format!(r#"<span class="grammar-symbol">{}</span>"#, s)
+        format!(r#"<span class="grammar-symbol">{}</span>"#, self)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parsing::character_class;
     use crate::Span;
 
     #[test]
@@ -255,12 +528,44 @@ mod tests {
                         Expression {
                             span: Span { start: 2, end: 3 },
                             kind: ExpressionKind::NonTerminal("a".to_string()),
-                        }
-            }.to_html(input, &used_by),
+                        },
+                examples: vec![],
+                doc: vec![],
+            }.to_html(input, &used_by, false, None),
             "<div class=\"grammar-rule\"><div class=\"grammar-rule-declaration\"><span class=\"grammar-rule-id\" id=\"name\">name</span><span class=\"grammar-usedby\">(used by <a href=\"#other\">other</a>)</span></div><div class=\"grammar-rule-expression\"><a href=\"#a\">a</a></div></div>",
         );
     }
 
+    #[test]
+    fn test_rule_doc() {
+        let input = "# General\n#: A <number>,\n#: in base 10.\n#:\n#: Leading zeros are allowed.\na: [0-9]+\n";
+        let g = crate::parse(input).unwrap();
+        let html = g.rulesets[0].rules[0].to_html(input, &[], false, None);
+        assert!(html.contains(
+            "</div><div class=\"grammar-rule-doc\"><p>A &lt;number&gt;, in base 10.</p><p>Leading zeros are allowed.</p></div><div class=\"grammar-rule-expression\">"
+        ));
+    }
+
+    #[test]
+    fn test_rule_railroad() {
+        let input = "name: a";
+        let html = Rule {
+            span: Span { start: 0, end: 1 },
+            id: "name".to_string(),
+            expression: Expression {
+                span: Span { start: 2, end: 3 },
+                kind: ExpressionKind::NonTerminal("a".to_string()),
+            },
+            examples: vec![],
+            doc: vec![],
+        }
+        .to_html(input, &[], true, None);
+        assert!(
+            html.contains(r#"<div class="grammar-rule-railroad"><svg class="grammar-railroad""#)
+        );
+        assert!(html.ends_with("</svg></div></div>"));
+    }
+
     #[test]
     fn test_choice() {
         // one line
@@ -297,6 +602,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_coverage() {
+        let input = "name: a | b";
+        let rule = Rule {
+            span: Span { start: 0, end: 4 },
+            id: "name".to_string(),
+            expression: Expression {
+                span: Span { start: 6, end: 11 },
+                kind: ExpressionKind::Choice(vec![
+                    Expression {
+                        span: Span { start: 6, end: 7 },
+                        kind: ExpressionKind::NonTerminal("a".to_string()),
+                    },
+                    Expression {
+                        span: Span { start: 10, end: 11 },
+                        kind: ExpressionKind::NonTerminal("b".to_string()),
+                    },
+                ]),
+            },
+            examples: vec![],
+            doc: vec![],
+        };
+        let mut coverage = Coverage::default();
+        assert!(rule
+            .to_html(input, &[], false, Some(&coverage))
+            .starts_with(r#"<div class="grammar-rule grammar-uncovered">"#));
+
+        coverage.rules.insert("name".to_string());
+        coverage.alternatives.insert(Span { start: 6, end: 7 });
+        let html = rule.to_html(input, &[], false, Some(&coverage));
+        assert!(html.starts_with(r#"<div class="grammar-rule">"#));
+        assert!(html.contains(
+            r##"<a href="#a">a</a><span class="grammar-symbol">|</span><span class="grammar-uncovered"><a href="#b">b</a></span>"##
+        ));
+    }
+
     #[test]
     fn test_sequence() {
         // one line
@@ -376,20 +717,66 @@ mod tests {
             ExpressionKind::Literal("GET".to_string()).to_html(0, ""),
             r#"<span class="grammar-literal">GET</span>"#.to_string()
         );
+        assert_eq!(
+            ExpressionKind::Literal("\"\\\n".to_string()).to_html(0, ""),
+            r#"<span class="grammar-literal">"\\\n</span>"#.to_string()
+        );
     }
 
     #[test]
     fn test_regex() {
         assert_eq!(
-            ExpressionKind::Regex("[a-z]+".to_string()).to_html(0, ""),
+            ExpressionKind::Regex(character_class("[a-z]+", 0)).to_html(0, ""),
             r#"<span class="grammar-regex">[a-z]+</span>"#.to_string()
         );
         assert_eq!(
-            ExpressionKind::Regex("[<]".to_string()).to_html(0, ""),
+            ExpressionKind::Regex(character_class("[<]", 0)).to_html(0, ""),
             r#"<span class="grammar-regex">[&lt;]</span>"#.to_string()
         );
     }
 
+    #[test]
+    fn test_sections() {
+        let input = "# A\na: b\n## B\nb: c\n### C\nc: \"x\"\n# D\nd: \"y\"\n";
+        let g = crate::parse(input).unwrap();
+        let html = g.to_html(input, &HashMap::new(), "h2", true, false, None);
+        let headers = html
+            .match_indices("<h")
+            .map(|(i, _)| &html[i..i + 3])
+            .collect::<Vec<&str>>();
+        assert_eq!(headers, vec!["<h2", "<h3", "<h4", "<h2"]);
+        let sections = html
+            .match_indices("<div class=\"grammar-ruleset\">")
+            .map(|(i, _)| html[..i].matches("<div").count() - html[..i].matches("</div>").count())
+            .collect::<Vec<usize>>();
+        assert_eq!(sections, vec![0, 1, 2, 0]);
+        assert_eq!(html.matches("<div").count(), html.matches("</div>").count());
+
+        assert_eq!(
+            g.toc_to_html(),
+            "<nav class=\"grammar-toc\"><ul>\
+             <li><a href=\"#a\">A</a><ul>\
+             <li><a href=\"#b\">B</a><ul>\
+             <li><a href=\"#c\">C</a></li></ul></li></ul></li>\
+             <li><a href=\"#d\">D</a></li></ul></nav>"
+        );
+        assert_eq!(heading_tag("div", 2), "div");
+
+        let input =
+            "# Lexical\na: b\n## Lexical\nb: c\n# Lexical-2\nc: \"x\"\n# Lexical\nd: \"y\"\n";
+        let g = crate::parse(input).unwrap();
+        assert_eq!(
+            g.section_ids(),
+            vec!["lexical", "lexical-2", "lexical-2-2", "lexical-3"]
+        );
+        let html = format_html(&g, input, "h2", false, false, None, true);
+        for id in g.section_ids() {
+            assert_eq!(html.matches(&format!("id=\"{}\"", id)).count(), 1);
+            assert_eq!(html.matches(&format!("href=\"#{}\"", id)).count(), 1);
+        }
+        assert_eq!(heading_tag("h5", 3), "h6");
+    }
+
     #[test]
     fn test_comment_to_id() {
         assert_eq!(
@@ -0,0 +1,247 @@
+use super::{encode_attribute, encode_html};
+use crate::Prose;
+
+impl Prose {
+    /// Converts the light Markdown of the prose to HTML.
+    ///
+    /// Paragraphs are separated by empty lines, list items start with `-`, `*` or a number
+    /// followed by a dot, and the inline elements are `*emphasis*`, `**strong**`, `` `code` ``
+    /// and `[links](url)`, a link being rendered as text if its URL is neither relative
+    /// nor starting with `http:`, `https:` or `mailto:`.
+    pub fn to_html(&self) -> String {
+        let mut s = r#"<div class="grammar-prose">"#.to_string();
+        for block in blocks(&self.value) {
+            match block {
+                Block::Paragraph(lines) => {
+                    s.push_str(format!("<p>{}</p>", inline_to_html(&lines.join(" "))).as_str())
+                }
+                Block::List(ordered, items) => {
+                    let tag = if ordered { "ol" } else { "ul" };
+                    s.push_str(format!("<{}>", tag).as_str());
+                    for item in items {
+                        s.push_str(format!("<li>{}</li>", inline_to_html(&item)).as_str());
+                    }
+                    s.push_str(format!("</{}>", tag).as_str());
+                }
+            }
+        }
+        s.push_str("</div>");
+        s
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Block {
+    Paragraph(Vec<String>),
+    // ordered or not, and the text of each item
+    List(bool, Vec<String>),
+}
+
+fn blocks(text: &str) -> Vec<Block> {
+    let mut blocks = vec![];
+    let mut current: Option<Block> = None;
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            blocks.extend(current.take());
+            continue;
+        }
+        if let Some((ordered, item)) = list_item(line) {
+            match &mut current {
+                Some(Block::List(o, items)) if *o == ordered => items.push(item.to_string()),
+                _ => {
+                    blocks.extend(current.take());
+                    current = Some(Block::List(ordered, vec![item.to_string()]));
+                }
+            }
+            continue;
+        }
+        // a line that is not an item continues the current block
+        match &mut current {
+            Some(Block::Paragraph(lines)) => lines.push(line.to_string()),
+            Some(Block::List(_, items)) => {
+                let last = items.last_mut().unwrap();
+                last.push(' ');
+                last.push_str(line);
+            }
+            None => current = Some(Block::Paragraph(vec![line.to_string()])),
+        }
+    }
+    blocks.extend(current);
+    blocks
+}
+
+// returns whether the item is ordered, and its text
+fn list_item(line: &str) -> Option<(bool, &str)> {
+    if let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        return Some((false, item.trim_start()));
+    }
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        if let Some(item) = line[digits..].strip_prefix(". ") {
+            return Some((true, item.trim_start()));
+        }
+    }
+    None
+}
+
+fn inline_to_html(text: &str) -> String {
+    let chars = text.chars().collect::<Vec<char>>();
+    let mut s = "".to_string();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\\' if i + 1 < chars.len() && chars[i + 1].is_ascii_punctuation() => {
+                s.push_str(&encode_html(&chars[i + 1].to_string()));
+                i += 2;
+                continue;
+            }
+            '`' => {
+                let delimiter = chars[i..].iter().take_while(|c| **c == '`').count();
+                let start = i + delimiter;
+                if let Some(end) = find(&chars, start, &vec!['`'; delimiter]) {
+                    let code = chars[start..end].iter().collect::<String>();
+                    s.push_str(format!("<code>{}</code>", encode_html(code.trim())).as_str());
+                    i = end + delimiter;
+                    continue;
+                }
+                s.push_str(&"`".repeat(delimiter));
+                i = start;
+                continue;
+            }
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                if let Some(end) = find(&chars, i + 2, &['*', '*']).filter(|end| *end > i + 2) {
+                    let inner = chars[i + 2..end].iter().collect::<String>();
+                    s.push_str(format!("<strong>{}</strong>", inline_to_html(&inner)).as_str());
+                    i = end + 2;
+                    continue;
+                }
+            }
+            // an underscore inside a word is not an emphasis
+            '*' | '_'
+                if (c == '*' || i == 0 || !chars[i - 1].is_alphanumeric())
+                    && chars.get(i + 1).is_some_and(|c| !c.is_whitespace()) =>
+            {
+                if let Some(end) = find(&chars, i + 1, &[c]).filter(|end| *end > i + 1) {
+                    let inner = chars[i + 1..end].iter().collect::<String>();
+                    s.push_str(format!("<em>{}</em>", inline_to_html(&inner)).as_str());
+                    i = end + 1;
+                    continue;
+                }
+            }
+            '[' => {
+                if let Some(link) = link(&chars, i) {
+                    let text = inline_to_html(&link.text);
+                    if is_safe_url(&link.url) {
+                        s.push_str(
+                            format!(r#"<a href="{}">{}</a>"#, encode_attribute(&link.url), text)
+                                .as_str(),
+                        );
+                    } else {
+                        s.push_str(&text);
+                    }
+                    i = link.end;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        s.push_str(&encode_html(&c.to_string()));
+        i += 1;
+    }
+    s
+}
+
+struct Link {
+    text: String,
+    url: String,
+    // offset following the link
+    end: usize,
+}
+
+// link "[text](url)" starting at the given offset
+fn link(chars: &[char], start: usize) -> Option<Link> {
+    let middle = find(chars, start + 1, &[']', '('])?;
+    let end = find(chars, middle + 2, &[')'])?;
+    Some(Link {
+        text: chars[start + 1..middle].iter().collect(),
+        url: chars[middle + 2..end].iter().collect(),
+        end: end + 1,
+    })
+}
+
+// a relative URL, or an URL with the http, https or mailto scheme
+// (not `javascript:` for instance)
+fn is_safe_url(url: &str) -> bool {
+    if url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return false;
+    }
+    match url.find([':', '/', '?', '#']) {
+        Some(i) if url[i..].starts_with(':') => matches!(
+            url[..i].to_ascii_lowercase().as_str(),
+            "http" | "https" | "mailto"
+        ),
+        _ => true,
+    }
+}
+
+// offset of the first occurrence of the pattern from the given offset
+fn find(chars: &[char], from: usize, pattern: &[char]) -> Option<usize> {
+    (from..chars.len()).find(|i| chars[*i..].starts_with(pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Span;
+
+    #[test]
+    fn test_blocks() {
+        assert_eq!(
+            blocks("First\nparagraph.\n\n- a\n- b\n  continued\n1. c\n\nLast"),
+            vec![
+                Block::Paragraph(vec!["First".to_string(), "paragraph.".to_string()]),
+                Block::List(false, vec!["a".to_string(), "b continued".to_string()]),
+                Block::List(true, vec!["c".to_string()]),
+                Block::Paragraph(vec!["Last".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inline() {
+        assert_eq!(
+            inline_to_html("A *rule* is **not** a `<token>`"),
+            "A <em>rule</em> is <strong>not</strong> a <code>&lt;token&gt;</code>"
+        );
+        assert_eq!(
+            inline_to_html("See [the *spec*](https://example.com/a_b) or snake_case_id"),
+            "See <a href=\"https://example.com/a_b\">the <em>spec</em></a> or snake_case_id"
+        );
+        assert_eq!(
+            inline_to_html("[a b](https://example.com/?a=1&b=\"2\")"),
+            "<a href=\"https://example.com/?a=1&amp;b=&quot;2&quot;\">a b</a>"
+        );
+        assert_eq!(
+            inline_to_html("[x](JavaScript:void) [y](#rules) [z](../a.html)"),
+            "x <a href=\"#rules\">y</a> <a href=\"../a.html\">z</a>"
+        );
+        assert_eq!(
+            inline_to_html("`` a`b `` \\*x\\* 2 * 3"),
+            "<code>a`b</code> *x* 2 * 3"
+        );
+    }
+
+    #[test]
+    fn test_prose() {
+        let prose = Prose {
+            span: Span { start: 0, end: 0 },
+            value: "An _expression_:\n\n* literal\n* `[a-z]`".to_string(),
+        };
+        assert_eq!(
+            prose.to_html(),
+            "<div class=\"grammar-prose\"><p>An <em>expression</em>:</p><ul><li>literal</li><li><code>[a-z]</code></li></ul></div>"
+        );
+    }
+}
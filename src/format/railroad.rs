@@ -0,0 +1,380 @@
+use super::{Expression, ExpressionKind, Grammar, Rule};
+use crate::Quantifier;
+
+const ARC_RADIUS: usize = 10;
+const VERTICAL_SEPARATION: usize = 8;
+const HORIZONTAL_SEPARATION: usize = 10;
+const CHAR_WIDTH: usize = 9;
+const BOX_HEIGHT: usize = 22;
+const BOX_PADDING: usize = 10;
+const PADDING: usize = 10;
+
+const STYLE: &str = "path { stroke-width: 2; stroke: black; fill: none; } \
+rect { stroke-width: 2; stroke: black; fill: white; } \
+text { font-family: monospace; font-size: 14px; text-anchor: middle; } \
+a text { fill: #20609f; }";
+
+/// Returns a standalone SVG railroad diagram for each rule of the grammar.
+///
+/// Non-terminal boxes link to the diagram file of the referenced rule (`<id>.svg`).
+pub fn format_railroad(g: &Grammar) -> Vec<(String, String)> {
+    g.get_rules()
+        .iter()
+        .map(|rule| (rule.id.clone(), rule.to_svg(true)))
+        .collect()
+}
+
+impl Rule {
+    /// Renders the railroad diagram of the rule.
+    ///
+    /// A standalone diagram embeds its own style and links to sibling `.svg` files,
+    /// otherwise it is meant to be inlined in the HTML output and links to the rule anchors.
+    pub fn to_svg(&self, standalone: bool) -> String {
+        let diagram = Diagram::from(&self.expression);
+        let width = diagram.width() + 2 * PADDING + 2 * HORIZONTAL_SEPARATION;
+        // leave room for the start and end markers
+        let up = diagram.up().max(BOX_HEIGHT / 2);
+        let down = diagram.down().max(BOX_HEIGHT / 2);
+        let height = up + down + 2 * PADDING;
+        let baseline = PADDING + up;
+
+        let mut s = if standalone {
+            format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" class="grammar-railroad" width="{width}" height="{height}" viewBox="0 0 {width} {height}"><style>{STYLE}</style>"#
+            )
+        } else {
+            format!(
+                r#"<svg class="grammar-railroad" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+            )
+        };
+
+        // start and end markers
+        let x = PADDING;
+        s.push_str(&format!(
+            r#"<path d="M{x} {} v{BOX_HEIGHT} M{x} {baseline} h{HORIZONTAL_SEPARATION}"/>"#,
+            baseline - BOX_HEIGHT / 2,
+        ));
+        let x = x + HORIZONTAL_SEPARATION;
+        s.push_str(&diagram.to_svg(x, baseline, standalone));
+        let x = x + diagram.width();
+        s.push_str(&format!(
+            r#"<path d="M{x} {baseline} h{HORIZONTAL_SEPARATION} m0 -{} v{BOX_HEIGHT}"/>"#,
+            BOX_HEIGHT / 2,
+        ));
+        s.push_str("</svg>");
+        s
+    }
+}
+
+/// Layout node of a railroad diagram.
+///
+/// Each node is entered on its left side and exited on its right side, on its baseline.
+/// `up` and `down` are the extents of the node above and below this baseline.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Diagram {
+    Terminal {
+        text: String,
+        class: &'static str,
+        href: Option<String>,
+    },
+    Skip,
+    Sequence(Vec<Diagram>),
+    Choice(Vec<Diagram>),
+    OneOrMore(Box<Diagram>),
+}
+
+impl From<&Expression> for Diagram {
+    fn from(expression: &Expression) -> Self {
+        match &expression.kind {
+            ExpressionKind::Choice(expressions) => {
+                Diagram::Choice(expressions.iter().map(Diagram::from).collect())
+            }
+            ExpressionKind::Sequence(expressions) => {
+                Diagram::Sequence(expressions.iter().map(Diagram::from).collect())
+            }
+            ExpressionKind::Group(expression) => Diagram::from(expression.as_ref()),
+            ExpressionKind::Negate(expression) => Diagram::Sequence(vec![
+                Diagram::Terminal {
+                    text: "~".to_string(),
+                    class: "grammar-railroad-symbol",
+                    href: None,
+                },
+                Diagram::from(expression.as_ref()),
+            ]),
+            ExpressionKind::Quantifier(expression, quantifier) => {
+                let diagram = Diagram::from(expression.as_ref());
+                match quantifier {
+                    Quantifier::ZeroOrOne => Diagram::Choice(vec![Diagram::Skip, diagram]),
+                    Quantifier::OneOrMany => Diagram::OneOrMore(Box::new(diagram)),
+                    Quantifier::Many => {
+                        Diagram::Choice(vec![Diagram::Skip, Diagram::OneOrMore(Box::new(diagram))])
+                    }
+                    // a loop followed by the bounds
+                    Quantifier::Repeat { min, max } => {
+                        let diagram = match max {
+                            Some(1) => diagram,
+                            _ => Diagram::OneOrMore(Box::new(diagram)),
+                        };
+                        let diagram = match min {
+                            0 => Diagram::Choice(vec![Diagram::Skip, diagram]),
+                            _ => diagram,
+                        };
+                        Diagram::Sequence(vec![
+                            diagram,
+                            Diagram::Terminal {
+                                text: quantifier.to_string(),
+                                class: "grammar-railroad-symbol",
+                                href: None,
+                            },
+                        ])
+                    }
+                }
+            }
+            ExpressionKind::Literal(_) => Diagram::Terminal {
+                text: expression.to_source(),
+                class: "grammar-railroad-literal",
+                href: None,
+            },
+            ExpressionKind::Regex(class) => Diagram::Terminal {
+                text: class.to_string(),
+                class: "grammar-railroad-regex",
+                href: None,
+            },
+            ExpressionKind::NonTerminal(id) => Diagram::Terminal {
+                text: id.clone(),
+                class: "grammar-railroad-nonterminal",
+                href: Some(id.clone()),
+            },
+        }
+    }
+}
+
+impl Diagram {
+    fn width(&self) -> usize {
+        match self {
+            Diagram::Terminal { text, .. } => text.chars().count() * CHAR_WIDTH + 2 * BOX_PADDING,
+            Diagram::Skip => 0,
+            Diagram::Sequence(items) => {
+                items.iter().map(|item| item.width()).sum::<usize>()
+                    + HORIZONTAL_SEPARATION * (items.len().max(1) - 1)
+            }
+            Diagram::Choice(items) => {
+                items.iter().map(|item| item.width()).max().unwrap_or(0) + 4 * ARC_RADIUS
+            }
+            Diagram::OneOrMore(item) => item.width() + 2 * ARC_RADIUS,
+        }
+    }
+
+    fn up(&self) -> usize {
+        match self {
+            Diagram::Terminal { .. } => BOX_HEIGHT / 2,
+            Diagram::Skip => 0,
+            Diagram::Sequence(items) => items.iter().map(|item| item.up()).max().unwrap_or(0),
+            Diagram::Choice(items) => items.first().map(|item| item.up()).unwrap_or(0),
+            Diagram::OneOrMore(item) => item.up(),
+        }
+    }
+
+    fn down(&self) -> usize {
+        match self {
+            Diagram::Terminal { .. } => BOX_HEIGHT / 2,
+            Diagram::Skip => 0,
+            Diagram::Sequence(items) => items.iter().map(|item| item.down()).max().unwrap_or(0),
+            Diagram::Choice(items) => {
+                let offsets = choice_offsets(items);
+                match (offsets.last(), items.last()) {
+                    (Some(offset), Some(item)) => offset + item.down(),
+                    _ => 0,
+                }
+            }
+            Diagram::OneOrMore(item) => loop_offset(item),
+        }
+    }
+
+    fn to_svg(&self, x: usize, y: usize, standalone: bool) -> String {
+        match self {
+            Diagram::Terminal { text, class, href } => {
+                let width = self.width();
+                let radius = if *class == "grammar-railroad-nonterminal" {
+                    0
+                } else {
+                    BOX_HEIGHT / 2
+                };
+                let s = format!(
+                    r#"<g class="{class}"><rect x="{x}" y="{}" width="{width}" height="{BOX_HEIGHT}" rx="{radius}"/><text x="{}" y="{}">{}</text></g>"#,
+                    y - BOX_HEIGHT / 2,
+                    x + width / 2,
+                    y + 5,
+                    encode_xml(text)
+                );
+                match href {
+                    None => s,
+                    Some(id) => {
+                        let href = if standalone {
+                            format!("{}.svg", id)
+                        } else {
+                            format!("#{}", id)
+                        };
+                        format!(
+                            r#"<a href="{href}" xlink:href="{href}">{s}</a>"#,
+                            href = encode_xml(&href)
+                        )
+                    }
+                }
+            }
+            Diagram::Skip => "".to_string(),
+            Diagram::Sequence(items) => {
+                let mut s = "".to_string();
+                let mut x = x;
+                for (i, item) in items.iter().enumerate() {
+                    s.push_str(&item.to_svg(x, y, standalone));
+                    x += item.width();
+                    if i + 1 < items.len() {
+                        s.push_str(&format!(r#"<path d="M{x} {y} h{HORIZONTAL_SEPARATION}"/>"#));
+                        x += HORIZONTAL_SEPARATION;
+                    }
+                }
+                s
+            }
+            Diagram::Choice(items) => {
+                let inner_width = self.width() - 4 * ARC_RADIUS;
+                let mut s = "".to_string();
+                for (item, offset) in items.iter().zip(choice_offsets(items)) {
+                    let item_x = x + 2 * ARC_RADIUS;
+                    let fill = inner_width - item.width();
+                    if offset == 0 {
+                        s.push_str(&format!(
+                            r#"<path d="M{x} {y} h{} M{} {y} h{}"/>"#,
+                            2 * ARC_RADIUS,
+                            item_x + item.width(),
+                            fill + 2 * ARC_RADIUS,
+                        ));
+                    } else {
+                        let vertical = offset - 2 * ARC_RADIUS;
+                        s.push_str(&format!(
+                            r#"<path d="M{x} {y} a{r} {r} 0 0 1 {r} {r} v{vertical} a{r} {r} 0 0 0 {r} {r} M{} {} h{fill} a{r} {r} 0 0 0 {r} -{r} v-{vertical} a{r} {r} 0 0 1 {r} -{r}"/>"#,
+                            item_x + item.width(),
+                            y + offset,
+                            r = ARC_RADIUS,
+                        ));
+                    }
+                    s.push_str(&item.to_svg(item_x, y + offset, standalone));
+                }
+                s
+            }
+            Diagram::OneOrMore(item) => {
+                let width = item.width();
+                let offset = loop_offset(item);
+                let vertical = offset - 2 * ARC_RADIUS;
+                let mut s = format!(
+                    r#"<path d="M{x} {y} h{r} M{} {y} h{r} a{r} {r} 0 0 1 {r} {r} v{vertical} a{r} {r} 0 0 1 -{r} {r} h-{width} a{r} {r} 0 0 1 -{r} -{r} v-{vertical} a{r} {r} 0 0 1 {r} -{r}"/>"#,
+                    x + ARC_RADIUS + width,
+                    r = ARC_RADIUS,
+                );
+                s.push_str(&item.to_svg(x + ARC_RADIUS, y, standalone));
+                s
+            }
+        }
+    }
+}
+
+// vertical offset of each alternative baseline relative to the choice baseline
+// the first alternative is on the baseline, the others are stacked below
+fn choice_offsets(items: &[Diagram]) -> Vec<usize> {
+    let mut offsets = vec![];
+    let mut bottom = 0;
+    for (i, item) in items.iter().enumerate() {
+        let offset = if i == 0 {
+            0
+        } else {
+            (bottom + VERTICAL_SEPARATION + item.up()).max(2 * ARC_RADIUS)
+        };
+        bottom = offset + item.down();
+        offsets.push(offset);
+    }
+    offsets
+}
+
+// vertical offset of the loop back line below the baseline
+fn loop_offset(item: &Diagram) -> usize {
+    (item.down() + VERTICAL_SEPARATION).max(2 * ARC_RADIUS)
+}
+
+fn encode_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::character_class;
+    use crate::Span;
+
+    fn expression(kind: ExpressionKind) -> Expression {
+        Expression {
+            span: Span { start: 0, end: 0 },
+            kind,
+        }
+    }
+
+    fn non_terminal(id: &str) -> Expression {
+        expression(ExpressionKind::NonTerminal(id.to_string()))
+    }
+
+    #[test]
+    fn test_layout() {
+        let terminal = Diagram::from(&expression(ExpressionKind::Literal("ab".to_string())));
+        assert_eq!(terminal.width(), 4 * CHAR_WIDTH + 2 * BOX_PADDING);
+        assert_eq!(terminal.up(), 11);
+        assert_eq!(terminal.down(), 11);
+
+        let choice = Diagram::from(&expression(ExpressionKind::Choice(vec![
+            non_terminal("a"),
+            non_terminal("bcd"),
+        ])));
+        assert_eq!(
+            choice.width(),
+            3 * CHAR_WIDTH + 2 * BOX_PADDING + 4 * ARC_RADIUS
+        );
+        assert_eq!(choice.up(), 11);
+        assert_eq!(choice.down(), 11 + VERTICAL_SEPARATION + 11 + 11);
+
+        let optional = Diagram::from(&expression(ExpressionKind::Quantifier(
+            Box::new(non_terminal("a")),
+            Quantifier::ZeroOrOne,
+        )));
+        assert_eq!(optional.up(), 0);
+        assert_eq!(choice_offsets(&[Diagram::Skip, terminal]), vec![0, 20]);
+    }
+
+    #[test]
+    fn test_non_terminal_link() {
+        let diagram = Diagram::from(&non_terminal("a"));
+        assert_eq!(
+            diagram.to_svg(0, 11, false),
+            r##"<a href="#a" xlink:href="#a"><g class="grammar-railroad-nonterminal"><rect x="0" y="0" width="29" height="22" rx="0"/><text x="14" y="16">a</text></g></a>"##
+        );
+        assert!(diagram
+            .to_svg(0, 11, true)
+            .starts_with(r#"<a href="a.svg" xlink:href="a.svg">"#));
+    }
+
+    #[test]
+    fn test_rule() {
+        let rule = Rule {
+            span: Span { start: 0, end: 7 },
+            id: "name".to_string(),
+            expression: expression(ExpressionKind::Regex(character_class("[<&]", 0))),
+            examples: vec![],
+            doc: vec![],
+        };
+        let svg = rule.to_svg(true);
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg""#));
+        assert!(svg.contains("<text x=\"48\" y=\"26\">[&lt;&amp;]</text>"));
+        assert!(svg.ends_with("</svg>"));
+        assert!(!rule.to_svg(false).contains("<style>"));
+    }
+}
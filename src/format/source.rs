@@ -0,0 +1,328 @@
+use super::{Comment, Example, Expression, ExpressionKind, Grammar, Rule, RuleSet};
+use crate::{DocComment, Include, Prose};
+use std::fmt;
+
+/// Maximum width of a rule before its choices are split, one alternative per line.
+const MAX_WIDTH: usize = 80;
+
+const INDENT: &str = "    ";
+
+pub fn format_source(g: &Grammar) -> String {
+    g.to_source()
+}
+
+impl Grammar {
+    /// Prints the grammar back to its text syntax, with a canonical layout.
+    ///
+    /// The include directives stay before or between the sections where they are,
+    /// separated from the sections by an empty line.
+    pub fn to_source(&self) -> String {
+        let mut s = "".to_string();
+        let mut includes = self.includes.iter().peekable();
+        for ruleset in &self.rulesets {
+            let start = ruleset.comment.span.start;
+            let mut included = false;
+            while let Some(include) = includes.next_if(|include| include.span.start < start) {
+                if !included && !s.is_empty() {
+                    s.push('\n');
+                }
+                included = true;
+                s.push_str(&include.to_source());
+                s.push('\n');
+            }
+            if !s.is_empty() {
+                s.push_str(if included { "\n" } else { "\n\n" });
+            }
+            s.push_str(&ruleset.to_source());
+        }
+        for (i, include) in includes.enumerate() {
+            if i == 0 && !s.is_empty() {
+                s.push('\n');
+            }
+            s.push_str(&include.to_source());
+            s.push('\n');
+        }
+        s
+    }
+}
+
+impl Include {
+    pub fn to_source(&self) -> String {
+        format!("@include \"{}\"", source_literal(&self.path))
+    }
+}
+
+impl RuleSet {
+    pub fn to_source(&self) -> String {
+        let mut s = self.comment.to_source();
+        s.push('\n');
+        if let Some(prose) = &self.prose {
+            s.push('\n');
+            s.push_str(&prose.to_source());
+            s.push('\n');
+        }
+        for rule in &self.rules {
+            s.push('\n');
+            s.push_str(&rule.to_source());
+            s.push('\n');
+        }
+        s
+    }
+}
+
+impl Comment {
+    pub fn to_source(&self) -> String {
+        let marker = "#".repeat(self.level);
+        if self.value.is_empty() {
+            marker
+        } else {
+            format!("{} {}", marker, self.value)
+        }
+    }
+}
+
+impl Rule {
+    pub fn to_source(&self) -> String {
+        let mut s = self
+            .doc
+            .iter()
+            .map(|doc| format!("{}\n", doc.to_source()))
+            .collect::<String>();
+        s.push_str(&self.declaration_to_source());
+        for example in &self.examples {
+            s.push('\n');
+            s.push_str(&example.to_source());
+        }
+        s
+    }
+
+    fn declaration_to_source(&self) -> String {
+        let s = format!("{}: {}", self.id, self.expression.to_source());
+        if s.chars().count() <= MAX_WIDTH {
+            return s;
+        }
+        match &self.expression.kind {
+            // a continuation line must be indented
+            // otherwise the newline ends the rule
+            ExpressionKind::Choice(expressions) => format!(
+                "{}: {}",
+                self.id,
+                expressions
+                    .iter()
+                    .map(|e| e.to_source())
+                    .collect::<Vec<String>>()
+                    .join(format!("\n{}| ", INDENT).as_str())
+            ),
+            _ => s,
+        }
+    }
+}
+
+impl Prose {
+    pub fn to_source(&self) -> String {
+        format!("\"\"\"\n{}\n\"\"\"", self.value)
+    }
+}
+
+impl DocComment {
+    pub fn to_source(&self) -> String {
+        if self.value.is_empty() {
+            "#:".to_string()
+        } else {
+            format!("#: {}", self.value)
+        }
+    }
+}
+
+impl Example {
+    pub fn to_source(&self) -> String {
+        if self.value.is_empty() {
+            "#>".to_string()
+        } else {
+            format!("#> {}", self.value)
+        }
+    }
+}
+
+impl Expression {
+    pub fn to_source(&self) -> String {
+        self.kind.to_source()
+    }
+}
+
+impl ExpressionKind {
+    pub fn to_source(&self) -> String {
+        match self {
+            ExpressionKind::Choice(expressions) => expressions
+                .iter()
+                .map(|e| e.to_source())
+                .collect::<Vec<String>>()
+                .join(" | "),
+            ExpressionKind::Sequence(expressions) => expressions
+                .iter()
+                .map(|e| e.to_source())
+                .collect::<Vec<String>>()
+                .join(" "),
+            ExpressionKind::Group(expression) => format!("({})", expression.to_source()),
+            ExpressionKind::Negate(expression) => format!("~{}", expression.to_source()),
+            ExpressionKind::Quantifier(expression, quantifier) => {
+                format!("{}{}", expression.to_source(), quantifier)
+            }
+            ExpressionKind::Literal(value) => format!("\"{}\"", source_literal(value)),
+            ExpressionKind::Regex(class) => class.to_string(),
+            ExpressionKind::NonTerminal(id) => id.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Grammar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_source())
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_source())
+    }
+}
+
+// escapes the value so that it is scanned back unchanged
+fn source_literal(value: &str) -> String {
+    escape_literal(value).replace('"', "\\\"")
+}
+
+// escapes the backslashes and the control chars of the value, not the double quotes
+pub(super) fn escape_literal(value: &str) -> String {
+    let mut s = "".to_string();
+    for c in value.chars() {
+        match c {
+            '\\' => s.push_str("\\\\"),
+            '\n' => s.push_str("\\n"),
+            '\r' => s.push_str("\\r"),
+            '\t' => s.push_str("\\t"),
+            '\0' => s.push_str("\\0"),
+            c if c.is_control() => s.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => s.push(c),
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::character_class;
+    use crate::{parse, Quantifier, Span};
+
+    fn expression(kind: ExpressionKind) -> Expression {
+        Expression {
+            span: Span { start: 0, end: 0 },
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_expression() {
+        assert_eq!(
+            ExpressionKind::Sequence(vec![
+                expression(ExpressionKind::Negate(Box::new(expression(
+                    ExpressionKind::Literal("a".to_string())
+                )))),
+                expression(ExpressionKind::Quantifier(
+                    Box::new(expression(ExpressionKind::Group(Box::new(expression(
+                        ExpressionKind::Choice(vec![
+                            expression(ExpressionKind::NonTerminal("b".to_string())),
+                            expression(ExpressionKind::Regex(character_class("[c-d]", 0))),
+                        ])
+                    ))))),
+                    Quantifier::Many
+                )),
+            ])
+            .to_source(),
+            r#"~"a" (b | [c-d])*"#
+        );
+    }
+
+    #[test]
+    fn test_literal() {
+        assert_eq!(source_literal("abc"), "abc");
+        assert_eq!(source_literal("\""), "\\\"");
+        assert_eq!(source_literal("\\"), "\\\\");
+        assert_eq!(source_literal("\\n"), "\\\\n");
+        assert_eq!(source_literal("\n\r\t\0"), "\\n\\r\\t\\0");
+        assert_eq!(source_literal("\u{1b}é"), "\\u{1b}é");
+        assert_eq!(source_literal("\\\""), "\\\\\\\"");
+    }
+
+    #[test]
+    fn test_long_choice() {
+        let rule = Rule {
+            span: Span { start: 0, end: 0 },
+            id: "rule".to_string(),
+            expression: expression(ExpressionKind::Choice(vec![
+                expression(ExpressionKind::NonTerminal("a".repeat(40))),
+                expression(ExpressionKind::NonTerminal("b".repeat(40))),
+            ])),
+            examples: vec![],
+            doc: vec![],
+        };
+        assert_eq!(
+            rule.to_source(),
+            format!("rule: {}\n    | {}", "a".repeat(40), "b".repeat(40))
+        );
+    }
+
+    #[test]
+    fn test_grammar() {
+        let input = "#   General  \nrule1:   a|b{1,2}  c\n\n\n\na: \"\\\"\" ~[\\n]+ | (b)?\nb: \"x\"{2,}\n# Other\nc: a [a]{3,3}\n";
+        let expected = "# General\n\nrule1: a | b{1,2} c\n\na: \"\\\"\" ~[\\n]+ | (b)?\n\nb: \"x\"{2,}\n\n\n# Other\n\nc: a [a]{3}\n";
+        let g = parse(input).unwrap();
+        assert_eq!(g.to_string(), expected);
+        assert_eq!(parse(expected).unwrap().to_string(), expected);
+    }
+
+    #[test]
+    fn test_include() {
+        let input = "@include \"a.grammar\"\n# General\na: b\n@include \"b.grammar\"\n@include \"c.grammar\"\n# Other\nc: d\n@include \"d.grammar\"";
+        let expected = "@include \"a.grammar\"\n\n# General\n\na: b\n\n@include \"b.grammar\"\n@include \"c.grammar\"\n\n# Other\n\nc: d\n\n@include \"d.grammar\"\n";
+        assert_eq!(parse(input).unwrap().to_string(), expected);
+        assert_eq!(parse(expected).unwrap().to_string(), expected);
+    }
+
+    #[test]
+    fn test_doc() {
+        let input = "# General\n\n#:A number\n#:\n  #: in base 10\na: [0-9]+\n";
+        let expected = "# General\n\n#: A number\n#:\n#: in base 10\na: [0-9]+\n";
+        assert_eq!(parse(input).unwrap().to_string(), expected);
+    }
+
+    #[test]
+    fn test_prose() {
+        let input = "# General\n\"\"\"  \nA *general* rule.\n\n- one\n\"\"\"\na: \"x\"\n";
+        let expected = "# General\n\n\"\"\"\nA *general* rule.\n\n- one\n\"\"\"\n\na: \"x\"\n";
+        assert_eq!(parse(input).unwrap().to_string(), expected);
+        assert_eq!(parse(expected).unwrap().to_string(), expected);
+    }
+
+    #[test]
+    fn test_sections() {
+        let input = "#Grammar\n##   Rules\na: b\n###\nb: \"x\"\n";
+        let expected = "# Grammar\n\n\n## Rules\n\na: b\n\n\n###\n\nb: \"x\"\n";
+        let g = parse(input).unwrap();
+        let levels = g
+            .rulesets
+            .iter()
+            .map(|ruleset| ruleset.comment.level)
+            .collect::<Vec<usize>>();
+        assert_eq!(levels, vec![1, 2, 3]);
+        assert_eq!(g.to_string(), expected);
+    }
+
+    #[test]
+    fn test_examples() {
+        let input = "# General\n\na: \"x\"*\n#>xx\n#>\n";
+        let expected = "# General\n\na: \"x\"*\n#> xx\n#>\n";
+        assert_eq!(parse(input).unwrap().to_string(), expected);
+    }
+}
@@ -0,0 +1,123 @@
+use super::ExpressionKind;
+use crate::ParseTree;
+
+// text of the node, shown for the leaves only
+fn node_text(node: &ParseTree, input: &str) -> String {
+    let text = input
+        .chars()
+        .skip(node.span.start)
+        .take(node.span.end - node.span.start)
+        .collect::<String>();
+    ExpressionKind::Literal(text).to_source()
+}
+
+impl ParseTree {
+    /// Returns the tree as an indented text, one node per line.
+    pub fn to_text(&self, input: &str) -> String {
+        let mut s = "".to_string();
+        self.push_text(input, 0, &mut s);
+        s
+    }
+
+    fn push_text(&self, input: &str, indent: usize, s: &mut String) {
+        s.push_str(&format!(
+            "{}{} {}..{}",
+            "  ".repeat(indent),
+            self.rule,
+            self.span.start,
+            self.span.end
+        ));
+        if self.children.is_empty() {
+            s.push_str(&format!(" {}", node_text(self, input)));
+        }
+        s.push('\n');
+        for child in &self.children {
+            child.push_text(input, indent + 1, s);
+        }
+    }
+
+    /// Returns the tree as an S-expression.
+    pub fn to_sexp(&self, input: &str) -> String {
+        if self.children.is_empty() {
+            return format!("({} {})", self.rule, node_text(self, input));
+        }
+        let children = self
+            .children
+            .iter()
+            .map(|child| child.to_sexp(input))
+            .collect::<Vec<String>>();
+        format!("({} {})", self.rule, children.join(" "))
+    }
+
+    /// Returns the tree as a JSON object, spans being given in chars.
+    pub fn to_json(&self, input: &str) -> String {
+        let mut s = format!(
+            r#"{{"rule":{},"span":{{"start":{},"end":{}}}"#,
+            json_string(&self.rule),
+            self.span.start,
+            self.span.end
+        );
+        if self.children.is_empty() {
+            let text = input
+                .chars()
+                .skip(self.span.start)
+                .take(self.span.end - self.span.start)
+                .collect::<String>();
+            s.push_str(&format!(r#","text":{}"#, json_string(&text)));
+        }
+        let children = self
+            .children
+            .iter()
+            .map(|child| child.to_json(input))
+            .collect::<Vec<String>>();
+        s.push_str(&format!(r#","children":[{}]}}"#, children.join(",")));
+        s
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut s = "\"".to_string();
+    for c in value.chars() {
+        match c {
+            '"' => s.push_str("\\\""),
+            '\\' => s.push_str("\\\\"),
+            '\n' => s.push_str("\\n"),
+            '\r' => s.push_str("\\r"),
+            '\t' => s.push_str("\\t"),
+            c if (c as u32) < 0x20 => s.push_str(&format!("\\u{:04x}", c as u32)),
+            c => s.push(c),
+        }
+    }
+    s.push('"');
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse;
+
+    #[test]
+    fn test_tree() {
+        let g = parse(
+            "# General\n\
+             sum: number (\"+\" number)*\n\
+             number: [0-9]+ | string\n\
+             string: \"\\\"\" [0-9] \"\\\"\"\n",
+        )
+        .unwrap();
+        let input = "1+23+\"4\"";
+        let node = g.interpret("sum", input).unwrap();
+        assert_eq!(
+            node.to_text(input),
+            "sum 0..8\n  number 0..1 \"1\"\n  number 2..4 \"23\"\n  number 5..8\n    string 5..8 \"\\\"4\\\"\"\n"
+        );
+        assert_eq!(
+            node.to_sexp(input),
+            "(sum (number \"1\") (number \"23\") (number (string \"\\\"4\\\"\")))"
+        );
+        assert_eq!(
+            node.children[2].to_json(input),
+            r#"{"rule":"number","span":{"start":5,"end":8},"children":[{"rule":"string","span":{"start":5,"end":8},"text":"\"4\"","children":[]}]}"#
+        );
+    }
+}
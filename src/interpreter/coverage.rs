@@ -0,0 +1,115 @@
+use super::{MatchError, ParseTree};
+use crate::{Expression, ExpressionKind, Grammar, Rule, Span};
+use std::collections::HashSet;
+
+/// Rules and choice alternatives (identified by their span) used by the derivations of some inputs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Coverage {
+    pub rules: HashSet<String>,
+    pub alternatives: HashSet<Span>,
+}
+
+impl Grammar {
+    /// Matches the input like `interpret`, and adds its derivation to the coverage.
+    ///
+    /// Nothing is added when the input does not match.
+    pub fn cover(
+        &self,
+        start: &str,
+        input: &str,
+        coverage: &mut Coverage,
+    ) -> Result<ParseTree, MatchError> {
+        let (result, alternatives) = self.run(start, input);
+        if let Ok(tree) = &result {
+            coverage.add_tree(tree);
+            coverage.alternatives.extend(alternatives);
+        }
+        result
+    }
+
+    /// Returns the alternatives of all the choices with their rule, in the order of the grammar.
+    pub fn alternatives(&self) -> Vec<(&Rule, &Expression)> {
+        let mut alternatives = vec![];
+        for ruleset in &self.rulesets {
+            for rule in &ruleset.rules {
+                let mut expressions = vec![];
+                rule.expression.add_alternatives(&mut expressions);
+                alternatives.extend(expressions.into_iter().map(|e| (rule, e)));
+            }
+        }
+        alternatives
+    }
+}
+
+impl Coverage {
+    fn add_tree(&mut self, tree: &ParseTree) {
+        self.rules.insert(tree.rule.clone());
+        for child in &tree.children {
+            self.add_tree(child);
+        }
+    }
+}
+
+impl Expression {
+    fn add_alternatives<'a>(&'a self, alternatives: &mut Vec<&'a Expression>) {
+        match &self.kind {
+            ExpressionKind::Choice(expressions) => {
+                for expression in expressions {
+                    alternatives.push(expression);
+                    expression.add_alternatives(alternatives);
+                }
+            }
+            ExpressionKind::Sequence(expressions) => {
+                for expression in expressions {
+                    expression.add_alternatives(alternatives);
+                }
+            }
+            ExpressionKind::Group(expression)
+            | ExpressionKind::Negate(expression)
+            | ExpressionKind::Quantifier(expression, _) => {
+                expression.add_alternatives(alternatives)
+            }
+            ExpressionKind::Literal(_)
+            | ExpressionKind::Regex(_)
+            | ExpressionKind::NonTerminal(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_cover() {
+        // # General
+        // a: b | "y" c
+        // b: "x" | "z"
+        // c: "w"
+        let g = parse("# General\na: b | \"y\" c\nb: \"x\" | \"z\"\nc: \"w\"\n").unwrap();
+        let mut coverage = Coverage::default();
+        assert!(g.cover("a", "x", &mut coverage).is_ok());
+        assert!(g.cover("a", "yy", &mut coverage).is_err());
+        assert_eq!(
+            coverage.rules,
+            HashSet::from(["a".to_string(), "b".to_string()])
+        );
+        assert_eq!(
+            coverage.alternatives,
+            HashSet::from([Span { start: 13, end: 14 }, Span { start: 26, end: 29 }])
+        );
+        assert_eq!(
+            g.alternatives()
+                .iter()
+                .map(|(_, e)| e.span.clone())
+                .collect::<Vec<Span>>(),
+            vec![
+                Span { start: 13, end: 14 },
+                Span { start: 17, end: 22 },
+                Span { start: 26, end: 29 },
+                Span { start: 32, end: 35 },
+            ]
+        );
+    }
+}
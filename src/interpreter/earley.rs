@@ -0,0 +1,664 @@
+use super::MatchError;
+use crate::{CharacterClass, Expression, ExpressionKind, Grammar, Quantifier, Span};
+use std::collections::{HashMap, HashSet};
+
+/// Number of derivations of an input accepted by the Earley recogniser.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Derivations {
+    One,
+    Many,
+}
+
+impl Grammar {
+    /// Matches the whole input against the start rule with an Earley recogniser.
+    ///
+    /// Unlike `interpret`, choices are unordered, so any context-free grammar is accepted.
+    /// `~e` matches one char when no prefix of the input at this position derives `e`.
+    pub fn recognize(&self, start: &str, input: &str) -> Result<Derivations, MatchError> {
+        let bnf = Bnf::new(self);
+        let start = match bnf.ids.get(start) {
+            None => {
+                return Err(MatchError {
+                    span: Span { start: 0, end: 0 },
+                    message: format!("rule <{}> is not defined", start),
+                })
+            }
+            Some(start) => *start,
+        };
+        let mut recognizer = Recognizer {
+            bnf: &bnf,
+            input: input.chars().collect(),
+            negations: HashMap::new(),
+            completed: HashMap::new(),
+            derivations: HashMap::new(),
+        };
+        let sets = recognizer.chart(start, 0);
+        let len = recognizer.input.len();
+        if sets.len() == len + 1 && recognizer.is_completed(&sets[len], start, 0) {
+            for (j, set) in sets.iter().enumerate() {
+                for item in set {
+                    if item.dot == bnf.productions[item.production].rhs.len() {
+                        let lhs = bnf.productions[item.production].lhs;
+                        let origins = recognizer.completed.entry((lhs, j)).or_default();
+                        if !origins.contains(&item.origin) {
+                            origins.push(item.origin);
+                        }
+                    }
+                }
+            }
+            return match recognizer.evaluate(Task::Count(start, 0, len)) {
+                1 => Ok(Derivations::One),
+                _ => Ok(Derivations::Many),
+            };
+        }
+        Err(recognizer.error(&sets, start))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Symbol {
+    NonTerminal(usize),
+    Terminal(usize),
+}
+
+enum Terminal {
+    Char(char),
+    Class(CharacterClass),
+    // matches a char if the non-terminal does not derive a prefix of the input
+    Negate(usize),
+}
+
+struct Production {
+    lhs: usize,
+    rhs: Vec<Symbol>,
+}
+
+// the grammar expanded into plain productions over chars,
+// with a fresh non-terminal for each nested choice, quantifier and negation
+struct Bnf {
+    ids: HashMap<String, usize>,
+    productions: Vec<Production>,
+    // productions of each non-terminal
+    alternatives: Vec<Vec<usize>>,
+    nullable: Vec<bool>,
+    terminals: Vec<(Terminal, String)>,
+}
+
+impl Bnf {
+    fn new(g: &Grammar) -> Bnf {
+        let mut bnf = Bnf {
+            ids: HashMap::new(),
+            productions: vec![],
+            alternatives: vec![],
+            nullable: vec![],
+            terminals: vec![],
+        };
+        let mut rules = vec![];
+        for rule in g.get_rules() {
+            if !bnf.ids.contains_key(&rule.id) {
+                let non_terminal = bnf.non_terminal();
+                bnf.ids.insert(rule.id.clone(), non_terminal);
+                rules.push((non_terminal, rule));
+            }
+        }
+        for (non_terminal, rule) in rules {
+            bnf.add_expression(non_terminal, &rule.expression);
+        }
+
+        bnf.nullable = vec![false; bnf.alternatives.len()];
+        loop {
+            let mut changed = false;
+            for production in &bnf.productions {
+                if !bnf.nullable[production.lhs]
+                    && production.rhs.iter().all(|symbol| match symbol {
+                        Symbol::NonTerminal(id) => bnf.nullable[*id],
+                        Symbol::Terminal(_) => false,
+                    })
+                {
+                    bnf.nullable[production.lhs] = true;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        bnf
+    }
+
+    fn non_terminal(&mut self) -> usize {
+        self.alternatives.push(vec![]);
+        self.alternatives.len() - 1
+    }
+
+    fn add_production(&mut self, lhs: usize, rhs: Vec<Symbol>) {
+        self.alternatives[lhs].push(self.productions.len());
+        self.productions.push(Production { lhs, rhs });
+    }
+
+    fn add_expression(&mut self, lhs: usize, expression: &Expression) {
+        if let ExpressionKind::Choice(expressions) = &expression.kind {
+            for expression in expressions {
+                let rhs = self.symbols(expression);
+                self.add_production(lhs, rhs);
+            }
+        } else {
+            let rhs = self.symbols(expression);
+            self.add_production(lhs, rhs);
+        }
+    }
+
+    fn add_terminal(&mut self, terminal: Terminal, text: String) -> Symbol {
+        self.terminals.push((terminal, text));
+        Symbol::Terminal(self.terminals.len() - 1)
+    }
+
+    // a fresh non-terminal for the repetition of the body
+    //
+    // the repetitions are left-recursive, which keeps the Earley sets small
+    fn repeat(&mut self, body: Vec<Symbol>, quantifier: &Quantifier) -> Symbol {
+        let lhs = self.non_terminal();
+        let mut repeated = vec![Symbol::NonTerminal(lhs)];
+        repeated.extend(body.iter().cloned());
+        match quantifier {
+            Quantifier::ZeroOrOne => {
+                self.add_production(lhs, vec![]);
+                self.add_production(lhs, body);
+            }
+            Quantifier::Many => {
+                self.add_production(lhs, vec![]);
+                self.add_production(lhs, repeated);
+            }
+            Quantifier::OneOrMany => {
+                self.add_production(lhs, body);
+                self.add_production(lhs, repeated);
+            }
+            Quantifier::Repeat { min, max } => {
+                let mut powers = Powers::new(self, body.clone());
+                let mut rhs = powers.exactly(self, *min);
+                match max {
+                    None => rhs.push(self.repeat(body, &Quantifier::Many)),
+                    Some(max) if max > min => rhs.push(powers.at_most(self, max - min)),
+                    Some(_) => {}
+                }
+                self.add_production(lhs, rhs);
+            }
+        }
+        Symbol::NonTerminal(lhs)
+    }
+
+    fn symbols(&mut self, expression: &Expression) -> Vec<Symbol> {
+        match &expression.kind {
+            ExpressionKind::Choice(_) => {
+                let lhs = self.non_terminal();
+                self.add_expression(lhs, expression);
+                vec![Symbol::NonTerminal(lhs)]
+            }
+            ExpressionKind::Sequence(expressions) => expressions
+                .iter()
+                .flat_map(|expression| self.symbols(expression))
+                .collect(),
+            ExpressionKind::Group(expression) => self.symbols(expression),
+            ExpressionKind::Negate(negated) => {
+                let lhs = self.non_terminal();
+                self.add_expression(lhs, negated);
+                vec![self.add_terminal(Terminal::Negate(lhs), expression.to_source())]
+            }
+            ExpressionKind::Quantifier(body, quantifier) => {
+                let body = self.symbols(body);
+                vec![self.repeat(body, quantifier)]
+            }
+            ExpressionKind::Literal(value) => value
+                .chars()
+                .map(|c| {
+                    let text = ExpressionKind::Literal(c.to_string()).to_source();
+                    self.add_terminal(Terminal::Char(c), text)
+                })
+                .collect(),
+            ExpressionKind::Regex(class) => {
+                let class = class.clone();
+                let quantifier = class.quantifier.clone();
+                let terminal = self.add_terminal(Terminal::Class(class), expression.to_source());
+                match quantifier {
+                    None => vec![terminal],
+                    Some(quantifier) => vec![self.repeat(vec![terminal], &quantifier)],
+                }
+            }
+            ExpressionKind::NonTerminal(id) => match self.ids.get(id) {
+                Some(non_terminal) => vec![Symbol::NonTerminal(*non_terminal)],
+                // an undefined rule never matches
+                None => vec![Symbol::NonTerminal(self.non_terminal())],
+            },
+        }
+    }
+}
+
+// non-terminals deriving the body repeated a power of two times, so that a bounded repetition
+// only needs a number of productions logarithmic in its bounds
+struct Powers {
+    // non-terminal deriving the body repeated 2^i times
+    exactly: Vec<usize>,
+    // non-terminal deriving the body repeated less than 2^i times
+    less_than: Vec<usize>,
+}
+
+impl Powers {
+    fn new(bnf: &mut Bnf, body: Vec<Symbol>) -> Powers {
+        let once = bnf.non_terminal();
+        bnf.add_production(once, body);
+        let never = bnf.non_terminal();
+        bnf.add_production(never, vec![]);
+        Powers {
+            exactly: vec![once],
+            less_than: vec![never],
+        }
+    }
+
+    fn power(&mut self, bnf: &mut Bnf, i: usize) -> Symbol {
+        while self.exactly.len() <= i {
+            let last = Symbol::NonTerminal(*self.exactly.last().unwrap());
+            let lhs = bnf.non_terminal();
+            bnf.add_production(lhs, vec![last, last]);
+            self.exactly.push(lhs);
+        }
+        Symbol::NonTerminal(self.exactly[i])
+    }
+
+    // 0..2^i - 1 copies, the first alternative taking less than 2^(i-1) copies
+    // and the second one at least 2^(i-1), so that there is one derivation for each count
+    fn less_than(&mut self, bnf: &mut Bnf, i: usize) -> Symbol {
+        while self.less_than.len() <= i {
+            let j = self.less_than.len();
+            let previous = Symbol::NonTerminal(self.less_than[j - 1]);
+            let power = self.power(bnf, j - 1);
+            let lhs = bnf.non_terminal();
+            bnf.add_production(lhs, vec![previous]);
+            bnf.add_production(lhs, vec![power, previous]);
+            self.less_than.push(lhs);
+        }
+        Symbol::NonTerminal(self.less_than[i])
+    }
+
+    // the body repeated n times
+    fn exactly(&mut self, bnf: &mut Bnf, n: usize) -> Vec<Symbol> {
+        (0..usize::BITS as usize)
+            .filter(|i| n & (1 << i) != 0)
+            .map(|i| self.power(bnf, i))
+            .collect()
+    }
+
+    // the body repeated from 0 to n times, n > 0
+    fn at_most(&mut self, bnf: &mut Bnf, n: usize) -> Symbol {
+        // 2^i <= n < 2^(i+1)
+        let i = (usize::BITS - 1 - n.leading_zeros()) as usize;
+        let lhs = bnf.non_terminal();
+        let less_than = self.less_than(bnf, i);
+        bnf.add_production(lhs, vec![less_than]);
+        let mut rhs = vec![self.power(bnf, i)];
+        let rest = n - (1 << i);
+        if rest > 0 {
+            rhs.push(self.at_most(bnf, rest));
+        }
+        bnf.add_production(lhs, rhs);
+        Symbol::NonTerminal(lhs)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Item {
+    production: usize,
+    dot: usize,
+    origin: usize,
+}
+
+// a derivation count to evaluate
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Task {
+    // derivations of input[start..end] from the non-terminal
+    Count(usize, usize, usize),
+    // derivations of input[item.origin..end] from the symbols before the dot
+    Ways(Item, usize),
+}
+
+struct Recognizer<'a> {
+    bnf: &'a Bnf,
+    input: Vec<char>,
+    // (negation terminal, position) => match
+    negations: HashMap<(usize, usize), bool>,
+    // (non-terminal, end) => start of the completed items
+    completed: HashMap<(usize, usize), Vec<usize>>,
+    // derivation counts, saturated at 2 (None while being evaluated)
+    derivations: HashMap<Task, Option<usize>>,
+}
+
+// Earley sets being built
+struct Chart {
+    sets: Vec<Vec<Item>>,
+    seen: Vec<HashSet<Item>>,
+    // items of each set waiting for a non-terminal
+    waiting: Vec<HashMap<usize, Vec<Item>>>,
+}
+
+impl Chart {
+    fn add(&mut self, bnf: &Bnf, j: usize, item: Item) {
+        while self.sets.len() <= j {
+            self.sets.push(vec![]);
+            self.seen.push(HashSet::new());
+            self.waiting.push(HashMap::new());
+        }
+        if !self.seen[j].insert(item) {
+            return;
+        }
+        self.sets[j].push(item);
+        if let Some(Symbol::NonTerminal(id)) = bnf.productions[item.production].rhs.get(item.dot) {
+            self.waiting[j].entry(*id).or_default().push(item);
+        }
+    }
+}
+
+impl<'a> Recognizer<'a> {
+    // Earley sets from the position `from`, stopping at the first empty set
+    fn chart(&mut self, start: usize, from: usize) -> Vec<Vec<Item>> {
+        let bnf = self.bnf;
+        let mut chart = Chart {
+            sets: vec![],
+            seen: vec![],
+            waiting: vec![],
+        };
+        for production in &bnf.alternatives[start] {
+            let item = Item {
+                production: *production,
+                dot: 0,
+                origin: from,
+            };
+            chart.add(bnf, 0, item);
+        }
+
+        let mut j = 0;
+        while j < chart.sets.len() {
+            let pos = from + j;
+            let mut i = 0;
+            while i < chart.sets[j].len() {
+                let item = chart.sets[j][i];
+                i += 1;
+                let production = &bnf.productions[item.production];
+                let mut added = vec![];
+                match production.rhs.get(item.dot) {
+                    Some(Symbol::NonTerminal(id)) => {
+                        for production in &bnf.alternatives[*id] {
+                            added.push(Item {
+                                production: *production,
+                                dot: 0,
+                                origin: pos,
+                            });
+                        }
+                        if bnf.nullable[*id] {
+                            added.push(Item {
+                                dot: item.dot + 1,
+                                ..item
+                            });
+                        }
+                    }
+                    Some(Symbol::Terminal(id)) => {
+                        if self.matches(*id, pos) {
+                            let next = Item {
+                                dot: item.dot + 1,
+                                ..item
+                            };
+                            chart.add(bnf, j + 1, next);
+                        }
+                    }
+                    None => {
+                        let waiting = chart.waiting[item.origin - from].get(&production.lhs);
+                        for other in waiting.into_iter().flatten() {
+                            added.push(Item {
+                                dot: other.dot + 1,
+                                ..*other
+                            });
+                        }
+                    }
+                }
+                for item in added {
+                    chart.add(bnf, j, item);
+                }
+            }
+            if chart.sets.len() == j + 1 {
+                break;
+            }
+            j += 1;
+        }
+        chart.sets
+    }
+
+    fn is_completed(&self, set: &[Item], lhs: usize, origin: usize) -> bool {
+        set.iter().any(|item| {
+            let production = &self.bnf.productions[item.production];
+            production.lhs == lhs && item.origin == origin && item.dot == production.rhs.len()
+        })
+    }
+
+    fn matches(&mut self, terminal: usize, pos: usize) -> bool {
+        let c = match self.input.get(pos) {
+            None => return false,
+            Some(c) => *c,
+        };
+        match &self.bnf.terminals[terminal].0 {
+            Terminal::Char(value) => *value == c,
+            Terminal::Class(class) => class.matches(c),
+            Terminal::Negate(negated) => {
+                if let Some(value) = self.negations.get(&(terminal, pos)) {
+                    return *value;
+                }
+                // a negation depending on itself does not match
+                self.negations.insert((terminal, pos), false);
+                let sets = self.chart(*negated, pos);
+                let value = !sets.iter().any(|set| self.is_completed(set, *negated, pos));
+                self.negations.insert((terminal, pos), value);
+                value
+            }
+        }
+    }
+
+    fn error(&self, sets: &[Vec<Item>], start: usize) -> MatchError {
+        let furthest = sets.len() - 1;
+        let mut expected = vec![];
+        for item in &sets[furthest] {
+            if let Some(Symbol::Terminal(id)) =
+                self.bnf.productions[item.production].rhs.get(item.dot)
+            {
+                let text = &self.bnf.terminals[*id].1;
+                if !expected.contains(text) {
+                    expected.push(text.clone());
+                }
+            }
+        }
+        if self.is_completed(&sets[furthest], start, 0) {
+            expected.push("end of input".to_string());
+        }
+        let found = match self.input.get(furthest) {
+            None => "end of input".to_string(),
+            Some(c) => format!("{:?}", c),
+        };
+        MatchError {
+            span: Span {
+                start: furthest,
+                end: furthest,
+            },
+            message: format!("expected {}, found {}", expected.join(" or "), found),
+        }
+    }
+
+    // number of derivations of the task, saturated at 2
+    //
+    // the tasks are evaluated with an explicit stack, as the derivations can be as deep as the input
+    fn evaluate(&mut self, task: Task) -> usize {
+        let mut stack = vec![task];
+        while let Some(task) = stack.last().copied() {
+            if let Some(Some(_)) = self.derivations.get(&task) {
+                stack.pop();
+                continue;
+            }
+            self.derivations.insert(task, None);
+            let terms = self.terms(task);
+            let pending = terms
+                .iter()
+                .flatten()
+                .find(|factor| !self.derivations.contains_key(factor));
+            if let Some(factor) = pending {
+                stack.push(*factor);
+                continue;
+            }
+            let mut count = 0;
+            for factors in terms {
+                let mut product = 1;
+                for factor in factors {
+                    // a cycle gives an infinite number of derivations
+                    product *= self.derivations[&factor].unwrap_or(2);
+                }
+                count += product;
+            }
+            self.derivations.insert(task, Some(count.min(2)));
+            stack.pop();
+        }
+        self.derivations[&task].unwrap_or(2)
+    }
+
+    // the count of the task as a sum of products of other tasks (1 for an empty product)
+    fn terms(&mut self, task: Task) -> Vec<Vec<Task>> {
+        match task {
+            Task::Count(non_terminal, start, end) => self.bnf.alternatives[non_terminal]
+                .iter()
+                .map(|production| {
+                    let item = Item {
+                        production: *production,
+                        dot: self.bnf.productions[*production].rhs.len(),
+                        origin: start,
+                    };
+                    vec![Task::Ways(item, end)]
+                })
+                .collect(),
+            Task::Ways(item, end) if item.dot == 0 => {
+                if item.origin == end {
+                    vec![vec![]]
+                } else {
+                    vec![]
+                }
+            }
+            Task::Ways(item, end) => {
+                let previous = Item {
+                    dot: item.dot - 1,
+                    ..item
+                };
+                match self.bnf.productions[item.production].rhs[item.dot - 1] {
+                    Symbol::Terminal(id) => {
+                        if end > item.origin && self.matches(id, end - 1) {
+                            vec![vec![Task::Ways(previous, end - 1)]]
+                        } else {
+                            vec![]
+                        }
+                    }
+                    Symbol::NonTerminal(id) => match self.completed.get(&(id, end)) {
+                        None => vec![],
+                        Some(origins) => origins
+                            .iter()
+                            .filter(|middle| **middle >= item.origin)
+                            .map(|middle| {
+                                vec![Task::Count(id, *middle, end), Task::Ways(previous, *middle)]
+                            })
+                            .collect(),
+                    },
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_recognize() {
+        let g = parse(
+            "# General\n\
+             expr: expr \"+\" expr | term\n\
+             term: [0-9]+ | \"(\" expr \")\"\n\
+             list: (item \",\")* item?\n\
+             item: \"x\" | \"xy\"\n\
+             comment: \"/*\" (~\"*/\")* \"*/\"\n",
+        )
+        .unwrap();
+        assert_eq!(g.recognize("expr", "12"), Ok(Derivations::One));
+        assert_eq!(g.recognize("expr", "1+(2)"), Ok(Derivations::One));
+        assert_eq!(g.recognize("expr", "1+2+3"), Ok(Derivations::Many));
+        // unordered choice: "xy" is found even if "x" comes first
+        assert_eq!(g.recognize("list", "xy,x,"), Ok(Derivations::One));
+        assert_eq!(g.recognize("list", ""), Ok(Derivations::One));
+        assert_eq!(g.recognize("comment", "/* a * b */"), Ok(Derivations::One));
+    }
+
+    #[test]
+    fn test_recognize_bounded() {
+        let g = parse("# General\na: \"x\"{2,3} \"y\"{1,} [0-9]{2}\n").unwrap();
+        assert_eq!(g.recognize("a", "xxy12"), Ok(Derivations::One));
+        assert_eq!(g.recognize("a", "xxxyy12"), Ok(Derivations::One));
+        assert!(g.recognize("a", "xy12").is_err());
+        assert!(g.recognize("a", "xxxxy12").is_err());
+        assert!(g.recognize("a", "xxy1").is_err());
+    }
+
+    #[test]
+    fn test_recognize_long_input() {
+        let g = parse("# General\na: [0-9]+\nb: (\"x\" | \"y\")* \"z\"\n").unwrap();
+        let bnf = Bnf::new(&g);
+        let input = "7".repeat(5000);
+        let mut recognizer = Recognizer {
+            bnf: &bnf,
+            input: input.chars().collect(),
+            negations: HashMap::new(),
+            completed: HashMap::new(),
+            derivations: HashMap::new(),
+        };
+        // the repetitions being left-recursive, the sets do not grow with the input
+        let sets = recognizer.chart(bnf.ids["a"], 0);
+        assert_eq!(sets.len(), 5001);
+        assert!(sets.iter().all(|set| set.len() <= 4));
+
+        assert_eq!(g.recognize("a", &input), Ok(Derivations::One));
+        assert!(g.recognize("a", &format!("{}x", input)).is_err());
+        let input = format!("{}z", "xy".repeat(2500));
+        assert_eq!(g.recognize("b", &input), Ok(Derivations::One));
+    }
+
+    #[test]
+    fn test_recognize_large_bounds() {
+        let g = parse("# General\na: \"x\"{1000000}\nb: \"x\"{2,1000}\n").unwrap();
+        assert!(Bnf::new(&g).productions.len() < 100);
+        // one derivation for each number of repetitions
+        for n in (2..=40).chain([511, 512, 513, 999, 1000]) {
+            assert_eq!(g.recognize("b", &"x".repeat(n)), Ok(Derivations::One));
+        }
+        assert!(g.recognize("b", "x").is_err());
+        assert!(g.recognize("b", &"x".repeat(1001)).is_err());
+    }
+
+    #[test]
+    fn test_recognize_error() {
+        let g = parse("# General\nsum: [0-9] (\"+\" [0-9])*\n").unwrap();
+        assert_eq!(
+            g.recognize("sum", "1+2)"),
+            Err(MatchError {
+                span: Span { start: 3, end: 3 },
+                message: "expected \"+\" or end of input, found ')'".to_string(),
+            })
+        );
+        assert_eq!(
+            g.recognize("sum", "1+"),
+            Err(MatchError {
+                span: Span { start: 2, end: 2 },
+                message: "expected [0-9], found end of input".to_string(),
+            })
+        );
+    }
+}
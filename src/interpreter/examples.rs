@@ -0,0 +1,52 @@
+use crate::{Grammar, Span, ValidateError};
+
+impl Grammar {
+    /// Returns an error for each example that is not matched by its rule.
+    ///
+    /// The error is located at the failure position inside the example.
+    pub fn example_errors(&self) -> Vec<ValidateError> {
+        let mut errors = vec![];
+        for rule in self.get_rules() {
+            for example in &rule.examples {
+                if let Err(error) = self.interpret(&rule.id, &example.value) {
+                    // the value ends the example line
+                    let start = example.span.end - example.value.chars().count() + error.span.start;
+                    errors.push(ValidateError {
+                        span: Span { start, end: start },
+                        message: format!(
+                            "example of rule <{}> does not match: {}",
+                            rule.id, error.message
+                        ),
+                    });
+                }
+            }
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, Span, ValidateError};
+
+    #[test]
+    fn test_example_errors() {
+        let g = parse(
+            "# General\n\
+             sum: number (\"+\" number)*\n\
+             #> 1+2\n\
+             #> 1+x\n\
+             number: [0-9]+\n\
+             #> 42\n",
+        )
+        .unwrap();
+        assert_eq!(
+            g.example_errors(),
+            vec![ValidateError {
+                span: Span { start: 48, end: 48 },
+                message: "example of rule <sum> does not match: expected [0-9]+, found 'x'"
+                    .to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,352 @@
+use crate::{CharacterClass, Expression, ExpressionKind, Grammar, Quantifier, Rule};
+use std::collections::HashMap;
+
+/// Options of the random generator.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GenerateOptions {
+    pub seed: u64,
+    /// Depth of nested rules after which the shortest alternatives are chosen.
+    pub max_depth: usize,
+    /// Bounds of the unbounded repetitions `*`, `+` and `{m,}` (at least `m`).
+    pub min_repeat: usize,
+    pub max_repeat: usize,
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        GenerateOptions {
+            seed: 0,
+            max_depth: 16,
+            min_repeat: 0,
+            max_repeat: 3,
+        }
+    }
+}
+
+impl Grammar {
+    /// Returns a random string derived from the start rule,
+    /// or `None` if the start rule is not defined.
+    ///
+    /// `~e` generates a printable char such that `e` does not match from this char,
+    /// nor from the chars generated for the previous negations.
+    pub fn generate(&self, start: &str, options: &GenerateOptions) -> Option<String> {
+        let mut generator = Generator::new(self, options);
+        if !generator.rules.contains_key(start) {
+            return None;
+        }
+        let mut s = "".to_string();
+        generator.non_terminal(start, 0, &mut s);
+        Some(s)
+    }
+}
+
+// xorshift64*, enough to generate reproducible inputs
+struct Random {
+    state: u64,
+}
+
+impl Random {
+    fn new(seed: u64) -> Random {
+        Random {
+            state: seed ^ 0x9e37_79b9_7f4a_7c15,
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    // random value in min..=max
+    fn range(&mut self, min: usize, max: usize) -> usize {
+        if max <= min {
+            return min;
+        }
+        min + (self.next() % (max - min + 1) as u64) as usize
+    }
+
+    // a random printable ASCII char accepted by the predicate,
+    // or else the first printable Latin-1 char accepted
+    fn printable_char(&mut self, accept: impl Fn(char) -> bool) -> Option<char> {
+        for _ in 0..32 {
+            let c = self.range(' ' as usize, '~' as usize) as u8 as char;
+            if accept(c) {
+                return Some(c);
+            }
+        }
+        (' '..='~').chain('\u{a1}'..='\u{ff}').find(|c| accept(*c))
+    }
+}
+
+struct Generator<'a> {
+    g: &'a Grammar,
+    options: &'a GenerateOptions,
+    rules: HashMap<String, Rule>,
+    // height of the smallest derivation tree of each rule
+    heights: HashMap<String, usize>,
+    // offset of the chars generated by negations so far, with the negated expression
+    negations: Vec<(usize, Expression)>,
+    random: Random,
+}
+
+impl<'a> Generator<'a> {
+    fn new(g: &'a Grammar, options: &'a GenerateOptions) -> Generator<'a> {
+        let mut rules = HashMap::new();
+        for rule in g.get_rules() {
+            rules.entry(rule.id.clone()).or_insert(rule);
+        }
+        let mut heights = HashMap::new();
+        loop {
+            let mut changed = false;
+            for rule in rules.values() {
+                let height = height(&rule.expression, &heights);
+                if height < *heights.get(&rule.id).unwrap_or(&usize::MAX) {
+                    heights.insert(rule.id.clone(), height);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        Generator {
+            g,
+            options,
+            rules,
+            heights,
+            negations: vec![],
+            random: Random::new(options.seed),
+        }
+    }
+
+    fn non_terminal(&mut self, id: &str, depth: usize, s: &mut String) {
+        let rule = match self.rules.get(id) {
+            None => return,
+            Some(rule) => rule.clone(),
+        };
+        // a rule that can not derive a finite string is not expanded anymore
+        if depth >= self.options.max_depth && !self.heights.contains_key(id) {
+            return;
+        }
+        self.expression(&rule.expression, depth + 1, s);
+    }
+
+    fn expression(&mut self, expression: &Expression, depth: usize, s: &mut String) {
+        let limited = depth >= self.options.max_depth;
+        match &expression.kind {
+            ExpressionKind::Choice(expressions) => {
+                let expression = if limited {
+                    expressions
+                        .iter()
+                        .min_by_key(|e| height(e, &self.heights))
+                        .unwrap()
+                } else {
+                    &expressions[self.random.range(0, expressions.len() - 1)]
+                };
+                self.expression(expression, depth, s);
+            }
+            ExpressionKind::Sequence(expressions) => {
+                for expression in expressions {
+                    self.expression(expression, depth, s);
+                }
+            }
+            ExpressionKind::Group(expression) => self.expression(expression, depth, s),
+            ExpressionKind::Negate(negated) => {
+                if let Some(c) = self.negate(negated, s) {
+                    s.push(c);
+                }
+            }
+            ExpressionKind::Quantifier(expression, quantifier) => {
+                for _ in 0..self.repeat(quantifier, limited) {
+                    self.expression(expression, depth, s);
+                }
+            }
+            ExpressionKind::Literal(value) => s.push_str(value),
+            ExpressionKind::Regex(class) => {
+                let count = match &class.quantifier {
+                    None => 1,
+                    Some(quantifier) => self.repeat(quantifier, limited),
+                };
+                for _ in 0..count {
+                    if let Some(c) = self.class_char(class) {
+                        s.push(c);
+                    }
+                }
+            }
+            ExpressionKind::NonTerminal(id) => self.non_terminal(id, depth, s),
+        }
+    }
+
+    fn repeat(&mut self, quantifier: &Quantifier, limited: bool) -> usize {
+        let min = quantifier.min();
+        if limited {
+            return min;
+        }
+        match quantifier.max() {
+            Some(max) => self.random.range(min, max),
+            None => {
+                let min = min.max(self.options.min_repeat);
+                self.random.range(min, self.options.max_repeat.max(min))
+            }
+        }
+    }
+
+    fn class_char(&mut self, class: &CharacterClass) -> Option<char> {
+        let ranges = class.ranges();
+        if !class.negated && !ranges.is_empty() {
+            let (start, end) = ranges[self.random.range(0, ranges.len() - 1)];
+            let c = self.random.range(start as usize, end as usize) as u32;
+            return Some(char::from_u32(c).unwrap_or(start));
+        }
+        self.random.printable_char(|c| class.matches(c))
+    }
+
+    // a char where the negated expression does not match, given the string generated so far
+    // (a negated expression such as "*/" may also match from a previous negation)
+    fn negate(&mut self, negated: &Expression, s: &str) -> Option<char> {
+        // the negations too far behind to match up to the new char are not checked anymore
+        self.negations.retain(|(offset, negated)| {
+            max_length(negated).is_none_or(|length| s[*offset..].chars().count() < length)
+        });
+        self.negations.push((s.len(), negated.clone()));
+        let g = self.g;
+        let negations = &self.negations;
+        let c = self.random.printable_char(|c| {
+            negations.iter().rev().all(|(offset, negated)| {
+                let mut input = s[*offset..].to_string();
+                input.push(c);
+                !g.matches_prefix(negated, &input)
+            })
+        });
+        if c.is_none() {
+            self.negations.pop();
+        }
+        c
+    }
+}
+
+// number of chars of the longest string matched by the expression, if bounded
+// (a non-terminal is not bounded)
+fn max_length(expression: &Expression) -> Option<usize> {
+    match &expression.kind {
+        ExpressionKind::Choice(expressions) => expressions
+            .iter()
+            .map(max_length)
+            .try_fold(0, |max, length| Some(max.max(length?))),
+        ExpressionKind::Sequence(expressions) => expressions
+            .iter()
+            .map(max_length)
+            .try_fold(0, |sum, length| Some(sum + length?)),
+        ExpressionKind::Group(expression) => max_length(expression),
+        ExpressionKind::Quantifier(expression, quantifier) => {
+            Some(max_length(expression)? * quantifier.max()?)
+        }
+        ExpressionKind::Negate(_) => Some(1),
+        ExpressionKind::Literal(value) => Some(value.chars().count()),
+        ExpressionKind::Regex(class) => match &class.quantifier {
+            None => Some(1),
+            Some(quantifier) => quantifier.max(),
+        },
+        ExpressionKind::NonTerminal(_) => None,
+    }
+}
+
+fn height(expression: &Expression, heights: &HashMap<String, usize>) -> usize {
+    match &expression.kind {
+        ExpressionKind::Choice(expressions) => expressions
+            .iter()
+            .map(|e| height(e, heights))
+            .min()
+            .unwrap_or(0),
+        ExpressionKind::Sequence(expressions) => expressions
+            .iter()
+            .map(|e| height(e, heights))
+            .max()
+            .unwrap_or(0),
+        ExpressionKind::Group(expression) => height(expression, heights),
+        ExpressionKind::Quantifier(expression, quantifier) => match quantifier.min() {
+            0 => 0,
+            _ => height(expression, heights),
+        },
+        ExpressionKind::Negate(_) | ExpressionKind::Literal(_) | ExpressionKind::Regex(_) => 0,
+        ExpressionKind::NonTerminal(id) => match heights.get(id) {
+            None => usize::MAX,
+            Some(height) => height + 1,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_generate() {
+        let g = parse(
+            "# General\n\
+             sum: number (\"+\" number)*\n\
+             number: [0-9]+ | \"(\" sum \")\" | ~[0-9+()]\n\
+             comment: \"/*\" (~\"*/\")* \"*/\"\n",
+        )
+        .unwrap();
+        for seed in 0..50 {
+            let options = GenerateOptions {
+                seed,
+                max_depth: 4,
+                ..GenerateOptions::default()
+            };
+            let s = g.generate("sum", &options).unwrap();
+            assert!(g.interpret("sum", &s).is_ok(), "{:?}", s);
+            assert_eq!(g.generate("sum", &options), Some(s));
+
+            let s = g.generate("comment", &options).unwrap();
+            assert!(g.interpret("comment", &s).is_ok(), "{:?}", s);
+        }
+        assert_eq!(g.generate("expr", &GenerateOptions::default()), None);
+    }
+
+    #[test]
+    fn test_generate_accepted() {
+        let g = parse(
+            "# General\n\
+             file: (comment | string | ~(\"/*\" | \"\\\"\"))*\n\
+             comment: \"/*\" (~\"*/\")* \"*/\"\n\
+             string: \"\\\"\" (\"\\\\\" [\\\\\"] | ~[\\\\\"])* \"\\\"\"\n\
+             letters: (~([ -`c-~] | \"ab\"))* \"ab\"\n",
+        )
+        .unwrap();
+        for seed in 0..300 {
+            let options = GenerateOptions {
+                seed,
+                max_depth: 4,
+                min_repeat: 2,
+                max_repeat: 8,
+            };
+            for start in ["file", "letters"] {
+                let s = g.generate(start, &options).unwrap();
+                assert!(g.interpret(start, &s).is_ok(), "{:?}", s);
+            }
+        }
+    }
+
+    #[test]
+    fn test_printable_char() {
+        let mut random = Random::new(0);
+        assert_eq!(random.printable_char(|c| c == '\u{e9}'), Some('\u{e9}'));
+        assert_eq!(random.printable_char(|_| false), None);
+    }
+
+    #[test]
+    fn test_generate_repeat() {
+        let g = parse("# General\na: \"x\"+\n").unwrap();
+        let options = GenerateOptions {
+            min_repeat: 2,
+            max_repeat: 2,
+            ..GenerateOptions::default()
+        };
+        assert_eq!(g.generate("a", &options), Some("xx".to_string()));
+    }
+}
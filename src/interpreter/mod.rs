@@ -0,0 +1,10 @@
+mod coverage;
+mod earley;
+mod examples;
+mod generator;
+mod peg;
+
+pub use coverage::*;
+pub use earley::*;
+pub use generator::*;
+pub use peg::*;
@@ -0,0 +1,347 @@
+use crate::{Expression, ExpressionKind, Grammar, Rule, Span};
+use std::collections::{HashMap, HashSet};
+
+/// Parse tree: a rule matched over a span of the input (in chars).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseTree {
+    pub rule: String,
+    pub span: Span,
+    pub children: Vec<ParseTree>,
+}
+
+/// Error returned when an input does not match the grammar.
+///
+/// The span is the furthest position reached in the input, in chars.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatchError {
+    pub span: Span,
+    pub message: String,
+}
+
+impl Grammar {
+    /// Matches the whole input against the start rule, with a PEG semantics:
+    /// choices are ordered, quantifiers are greedy and never backtrack,
+    /// and `~e` consumes one char when `e` does not match at this position.
+    ///
+    /// Returns the parse tree of the start rule, with a node for each rule matched.
+    pub fn interpret(&self, start: &str, input: &str) -> Result<ParseTree, MatchError> {
+        self.run(start, input).0
+    }
+
+    // the result of `interpret`, with the spans of the choice alternatives of the derivation
+    pub(super) fn run(
+        &self,
+        start: &str,
+        input: &str,
+    ) -> (Result<ParseTree, MatchError>, Vec<Span>) {
+        let mut interpreter = Interpreter::new(self, input);
+        if !interpreter.rules.contains_key(start) {
+            let error = MatchError {
+                span: Span { start: 0, end: 0 },
+                message: format!("rule <{}> is not defined", start),
+            };
+            return (Err(error), vec![]);
+        }
+        match interpreter.non_terminal(start, 0) {
+            Some(end) if end == interpreter.input.len() => {
+                (Ok(interpreter.nodes.remove(0)), interpreter.alternatives)
+            }
+            result => {
+                if let Some(end) = result {
+                    interpreter.fail(end, "end of input".to_string());
+                }
+                (Err(interpreter.error()), vec![])
+            }
+        }
+    }
+
+    /// Returns true if the expression matches a prefix of the input.
+    pub(crate) fn matches_prefix(&self, expression: &Expression, input: &str) -> bool {
+        Interpreter::new(self, input)
+            .expression(expression, 0)
+            .is_some()
+    }
+}
+
+struct Interpreter {
+    rules: HashMap<String, Rule>,
+    input: Vec<char>,
+    // (rule, position) being matched, to stop left recursion
+    active: HashSet<(String, usize)>,
+    // failures are not recorded inside a negation
+    silent: usize,
+    furthest: usize,
+    expected: Vec<String>,
+    // nodes matched so far, not yet attached to their parent
+    nodes: Vec<ParseTree>,
+    // choice alternatives matched so far
+    alternatives: Vec<Span>,
+}
+
+impl Interpreter {
+    fn new(g: &Grammar, input: &str) -> Interpreter {
+        let mut rules = HashMap::new();
+        for rule in g.get_rules() {
+            rules.entry(rule.id.clone()).or_insert(rule);
+        }
+        Interpreter {
+            rules,
+            input: input.chars().collect(),
+            active: HashSet::new(),
+            silent: 0,
+            furthest: 0,
+            expected: vec![],
+            nodes: vec![],
+            alternatives: vec![],
+        }
+    }
+
+    fn error(&self) -> MatchError {
+        let span = Span {
+            start: self.furthest,
+            end: self.furthest,
+        };
+        let found = match self.input.get(self.furthest) {
+            None => "end of input".to_string(),
+            Some(c) => format!("{:?}", c),
+        };
+        let message = format!("expected {}, found {}", self.expected.join(" or "), found);
+        MatchError { span, message }
+    }
+
+    fn fail(&mut self, pos: usize, expected: String) {
+        if self.silent > 0 || pos < self.furthest {
+            return;
+        }
+        if pos > self.furthest {
+            self.furthest = pos;
+            self.expected.clear();
+        }
+        if !self.expected.contains(&expected) {
+            self.expected.push(expected);
+        }
+    }
+
+    // returns the position after the match
+    fn expression(&mut self, expression: &Expression, pos: usize) -> Option<usize> {
+        let len = (self.nodes.len(), self.alternatives.len());
+        let result = self.expression_kind(expression, pos);
+        if result.is_none() {
+            self.nodes.truncate(len.0);
+            self.alternatives.truncate(len.1);
+        }
+        result
+    }
+
+    fn expression_kind(&mut self, expression: &Expression, pos: usize) -> Option<usize> {
+        match &expression.kind {
+            ExpressionKind::Choice(expressions) => {
+                for expression in expressions {
+                    if let Some(end) = self.expression(expression, pos) {
+                        self.alternatives.push(expression.span.clone());
+                        return Some(end);
+                    }
+                }
+                None
+            }
+            ExpressionKind::Sequence(expressions) => {
+                let mut pos = pos;
+                for expression in expressions {
+                    pos = self.expression(expression, pos)?;
+                }
+                Some(pos)
+            }
+            ExpressionKind::Group(expression) => self.expression(expression, pos),
+            ExpressionKind::Negate(negated) => {
+                let len = (self.nodes.len(), self.alternatives.len());
+                self.silent += 1;
+                let result = self.expression(negated, pos);
+                self.silent -= 1;
+                self.nodes.truncate(len.0);
+                self.alternatives.truncate(len.1);
+                if result.is_none() && pos < self.input.len() {
+                    Some(pos + 1)
+                } else {
+                    self.fail(pos, expression.to_source());
+                    None
+                }
+            }
+            ExpressionKind::Quantifier(expression, quantifier) => {
+                let mut pos = pos;
+                for _ in 0..quantifier.min() {
+                    pos = self.expression(expression, pos)?;
+                }
+                let mut count = quantifier.min();
+                while quantifier.max().is_none_or(|max| count < max) {
+                    match self.expression(expression, pos) {
+                        // an empty match would be repeated forever
+                        Some(next) if next == pos => return Some(next),
+                        Some(next) => pos = next,
+                        None => break,
+                    }
+                    count += 1;
+                }
+                Some(pos)
+            }
+            ExpressionKind::Literal(value) => {
+                let mut end = pos;
+                for c in value.chars() {
+                    if self.input.get(end) != Some(&c) {
+                        self.fail(pos, expression.to_source());
+                        return None;
+                    }
+                    end += 1;
+                }
+                Some(end)
+            }
+            ExpressionKind::Regex(class) => {
+                let (min, max) = match &class.quantifier {
+                    None => (1, 1),
+                    Some(quantifier) => (quantifier.min(), quantifier.max().unwrap_or(usize::MAX)),
+                };
+                // the input is not scanned beyond the maximum
+                let count = self.input[pos..]
+                    .iter()
+                    .take_while(|c| class.matches(**c))
+                    .take(max)
+                    .count();
+                if count < min {
+                    self.fail(pos + count, expression.to_source());
+                    return None;
+                }
+                Some(pos + count)
+            }
+            ExpressionKind::NonTerminal(id) => self.non_terminal(id, pos),
+        }
+    }
+
+    fn non_terminal(&mut self, id: &str, pos: usize) -> Option<usize> {
+        let rule = self.rules.get(id)?.clone();
+        let key = (rule.id.clone(), pos);
+        if !self.active.insert(key.clone()) {
+            return None;
+        }
+        let len = self.nodes.len();
+        let result = self.expression(&rule.expression, pos);
+        self.active.remove(&key);
+        if let Some(end) = result {
+            let children = self.nodes.split_off(len);
+            self.nodes.push(ParseTree {
+                rule: rule.id,
+                span: Span { start: pos, end },
+                children,
+            });
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn grammar() -> Grammar {
+        parse(
+            "# General\n\
+             sum: number (\"+\" number)*\n\
+             number: [0-9]+ | \"(\" sum \")\"\n\
+             string: \"'\" (~\"'\")* \"'\"\n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_interpret_ok() {
+        let g = grammar();
+        assert!(g.interpret("sum", "1+23+(4+5)").is_ok());
+        assert!(g.interpret("string", "'a+b'").is_ok());
+        assert!(g.interpret("string", "''").is_ok());
+    }
+
+    #[test]
+    fn test_interpret_tree() {
+        let g = grammar();
+        let node = |rule: &str, start: usize, end: usize, children: Vec<ParseTree>| ParseTree {
+            rule: rule.to_string(),
+            span: Span { start, end },
+            children,
+        };
+        assert_eq!(
+            g.interpret("sum", "1+(2)"),
+            Ok(node(
+                "sum",
+                0,
+                5,
+                vec![
+                    node("number", 0, 1, vec![]),
+                    node(
+                        "number",
+                        2,
+                        5,
+                        vec![node("sum", 3, 4, vec![node("number", 3, 4, vec![])])]
+                    ),
+                ]
+            ))
+        );
+    }
+
+    #[test]
+    fn test_interpret_error() {
+        let g = grammar();
+        assert_eq!(
+            g.interpret("sum", "1+(2+a)"),
+            Err(MatchError {
+                span: Span { start: 5, end: 5 },
+                message: "expected [0-9]+ or \"(\", found 'a'".to_string(),
+            })
+        );
+        assert_eq!(
+            g.interpret("sum", "1+2)"),
+            Err(MatchError {
+                span: Span { start: 3, end: 3 },
+                message: "expected \"+\" or end of input, found ')'".to_string(),
+            })
+        );
+        assert_eq!(
+            g.interpret("string", "'ab"),
+            Err(MatchError {
+                span: Span { start: 3, end: 3 },
+                message: "expected ~\"'\" or \"'\", found end of input".to_string(),
+            })
+        );
+        assert_eq!(
+            g.interpret("expr", "1"),
+            Err(MatchError {
+                span: Span { start: 0, end: 0 },
+                message: "rule <expr> is not defined".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_bounded_quantifier() {
+        let g = parse("# General\nhex: \"#\" [0-9a-f]{2} (\"-\" [0-9]{1,}){0,2}\n").unwrap();
+        assert!(g.interpret("hex", "#0a").is_ok());
+        assert!(g.interpret("hex", "#0a-1-23").is_ok());
+        assert!(g.interpret("hex", "#0").is_err());
+        assert!(g.interpret("hex", "#0a1").is_err());
+        assert!(g.interpret("hex", "#0a-1-2-3").is_err());
+    }
+
+    #[test]
+    fn test_long_input() {
+        // a class without quantifier does not scan the rest of the input
+        let g = parse("# General\nnumber: [0-9]*\ndigits: ([0-9] | \" \")*\n").unwrap();
+        let input = "7".repeat(50_000);
+        assert!(g.interpret("digits", &input).is_ok());
+        assert!(g.interpret("number", &input).is_ok());
+    }
+
+    #[test]
+    fn test_ordered_choice() {
+        let g = parse("# General\na: (\"x\" | \"xy\") \"z\"\n").unwrap();
+        assert!(g.interpret("a", "xz").is_ok());
+        assert!(g.interpret("a", "xyz").is_err());
+    }
+}
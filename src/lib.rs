@@ -1,7 +1,9 @@
 pub use self::core::*;
 pub use self::format::*;
+pub use self::interpreter::*;
 pub use self::parsing::*;
 
 mod core;
 mod format;
+mod interpreter;
 mod parsing;
@@ -0,0 +1,129 @@
+use crate::find_position;
+use grammar::{Coverage, Grammar, MatchError};
+use std::path::{Path, PathBuf};
+
+// inputs of the corpus: files that must match in ok/, files that must not in err/
+// (with true for the files that must match)
+// one of the directories may be missing, but the corpus can not be empty
+fn corpus_files(dir: &Path) -> Result<Vec<(bool, PathBuf)>, String> {
+    let mut files = vec![];
+    let mut found = false;
+    for (expect_match, name) in [(true, "ok"), (false, "err")] {
+        let path = dir.join(name);
+        if !path.is_dir() {
+            continue;
+        }
+        found = true;
+        let read_error =
+            |error: std::io::Error| format!("unable to read {}: {}", path.display(), error);
+        let mut paths = vec![];
+        for entry in std::fs::read_dir(&path).map_err(read_error)? {
+            let entry = entry.map_err(read_error)?.path();
+            if entry.is_file() {
+                paths.push(entry);
+            }
+        }
+        paths.sort();
+        files.extend(paths.into_iter().map(|path| (expect_match, path)));
+    }
+    if !found {
+        return Err(format!(
+            "the corpus {} has no ok/ or err/ directory",
+            dir.display()
+        ));
+    }
+    if files.is_empty() {
+        return Err(format!("the corpus {} has no files", dir.display()));
+    }
+    Ok(files)
+}
+
+// match each file of the corpus, print the result of each file and a summary
+// the rules and alternatives used by the matching files are added to the coverage (not with earley)
+// returns true if all the files pass, or an error if the corpus can not be read
+pub fn run_corpus(
+    g: &Grammar,
+    start: &str,
+    dir: &Path,
+    earley: bool,
+    coverage: &mut Coverage,
+) -> Result<bool, String> {
+    let mut passed = 0;
+    let mut failed = 0;
+    for (expect_match, file) in corpus_files(dir)? {
+        let display = file.strip_prefix(dir).unwrap_or(&file).display();
+        // a file that can not be read fails, whether it must match or not
+        let input = match std::fs::read_to_string(&file) {
+            Ok(input) => input,
+            Err(error) => {
+                failed += 1;
+                println!("FAIL {}: unable to read the file: {}", display, error);
+                continue;
+            }
+        };
+        let result = if earley {
+            g.recognize(start, &input).map(|_| ())
+        } else {
+            g.cover(start, &input, coverage).map(|_| ())
+        };
+        match (expect_match, result) {
+            (true, Ok(())) | (false, Err(_)) => {
+                passed += 1;
+                println!("PASS {}", display);
+            }
+            (true, Err(MatchError { span, message })) => {
+                failed += 1;
+                let pos = find_position(&input, span.start);
+                println!("FAIL {}:{}:{}: {}", display, pos.line, pos.column, message);
+            }
+            (false, Ok(())) => {
+                failed += 1;
+                println!("FAIL {}: the input matches <{}>", display, start);
+            }
+        }
+    }
+    println!("{} passed, {} failed", passed, failed);
+    Ok(failed == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corpus_files() {
+        let dir = std::env::temp_dir().join(format!("grammar-corpus-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(
+            corpus_files(&dir),
+            Err(format!(
+                "the corpus {} has no ok/ or err/ directory",
+                dir.display()
+            ))
+        );
+
+        std::fs::create_dir_all(dir.join("err")).unwrap();
+        assert_eq!(
+            corpus_files(&dir),
+            Err(format!("the corpus {} has no files", dir.display()))
+        );
+
+        std::fs::write(dir.join("err").join("b"), "x").unwrap();
+        std::fs::write(dir.join("err").join("a"), "x").unwrap();
+        assert_eq!(
+            corpus_files(&dir),
+            Ok(vec![
+                (false, dir.join("err").join("a")),
+                (false, dir.join("err").join("b"))
+            ])
+        );
+
+        // a file that can not be read fails
+        let g = grammar::parse("# General\na: \"y\"\n").unwrap();
+        let mut coverage = Coverage::default();
+        assert_eq!(run_corpus(&g, "a", &dir, false, &mut coverage), Ok(true));
+        std::fs::write(dir.join("err").join("c"), [0xff, 0xfe]).unwrap();
+        assert_eq!(run_corpus(&g, "a", &dir, false, &mut coverage), Ok(false));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
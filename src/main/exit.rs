@@ -2,6 +2,8 @@ pub enum ExitCode {
     Success,
     ErrorParsing,
     ErrorValidation,
+    ErrorFormatting,
+    ErrorMatching,
 }
 
 impl ExitCode {
@@ -12,6 +14,8 @@ impl ExitCode {
             // used by default by clap
             ExitCode::ErrorParsing => 3,
             ExitCode::ErrorValidation => 4,
+            ExitCode::ErrorFormatting => 5,
+            ExitCode::ErrorMatching => 6,
         };
         std::process::exit(value)
     }
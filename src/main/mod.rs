@@ -1,16 +1,24 @@
 extern crate grammar;
 
+mod corpus;
 mod exit;
 mod options;
 mod pos;
 
+use corpus::run_corpus;
 use exit::*;
-use grammar::{format_html, parse};
+#[cfg(feature = "serde")]
+use grammar::format_json;
+use grammar::{
+    format_analysis, format_coverage, format_html, format_markdown, format_railroad, format_source,
+    parse, Coverage, Derivations, GenerateOptions, Grammar, SourceMap,
+};
 use options::*;
 use pos::Pos;
 use std::fs::File;
 use std::io;
 use std::io::Read;
+use std::path::PathBuf;
 
 fn get_content(options: &CliOptions) -> String {
     match options.input_file.clone() {
@@ -30,36 +38,248 @@ fn get_content(options: &CliOptions) -> String {
 
 fn main() {
     let options = parse_options();
-    let content = get_content(&options);
+    let mut source = SourceMap::new(options.input_file.as_deref(), &get_content(&options));
+    // the included files are not formatted
+    if !matches!(options.command, Command::Fmt { .. }) {
+        if let Err(error) = source.include() {
+            eprintln!("{}: {}", error.location, error.message);
+            ExitCode::ErrorParsing.exit()
+        }
+    }
+    let content = source.text.clone();
     let g = match parse(&content) {
         Ok(value) => value,
         Err(errors) => {
             for error in errors {
-                let pos = find_position(&content, error.span.start);
-                eprintln!("{}:{}: {}", pos.line, pos.column, error.message);
+                eprintln!("{}: {}", source.location(error.span.start), error.message);
             }
             ExitCode::ErrorParsing.exit()
         }
     };
 
-    let errors_validate = g.validate();
-    if !errors_validate.is_empty() {
-        for error in g.validate() {
-            let pos = find_position(&content, error.span.start);
-            eprintln!("{}:{}: {}", pos.line, pos.column, error.message);
+    if let Command::Fmt { check } = options.command {
+        fmt(&g, &content, &options, check)
+    }
+
+    if let Some(start) = &options.start {
+        check_start(&g, start);
+    }
+    // the Earley recogniser accepts any context-free grammar
+    let errors_validate = if matches!(
+        options.command,
+        Command::Parse { earley: true, .. } | Command::Test { earley: true, .. }
+    ) {
+        g.validate_context_free(&start_rule(&g, &options))
+    } else {
+        match &options.start {
+            None => g.validate(),
+            Some(start) => g.validate_from(start),
+        }
+    };
+    let conflicts = if options.ll1 {
+        g.ll1_conflicts(&start_rule(&g, &options))
+    } else {
+        vec![]
+    };
+    if !errors_validate.is_empty() || !conflicts.is_empty() {
+        for error in errors_validate {
+            eprintln!("{}: {}", source.location(error.span.start), error.message);
+        }
+        for conflict in conflicts {
+            let location = source.location(conflict.span.start);
+            eprintln!("{}: {}", location, conflict.message);
+            if let Some(span) = conflict.other_span {
+                let location = source.location(span.start);
+                eprintln!("{}: note: conflicting alternative", location);
+            }
+        }
+        ExitCode::ErrorValidation.exit()
+    }
+
+    if options.command == Command::Check {
+        let errors = g.example_errors();
+        for error in &errors {
+            eprintln!("{}: {}", source.location(error.span.start), error.message);
+        }
+        if !errors.is_empty() {
+            ExitCode::ErrorValidation.exit()
+        }
+        ExitCode::Success.exit()
+    }
+
+    if let Command::Parse { file, earley, tree } = &options.command {
+        parse_file(&g, &options, file, *earley, tree)
+    }
+
+    if let Command::Test {
+        corpus,
+        earley,
+        coverage: print_coverage,
+        coverage_html,
+    } = &options.command
+    {
+        let start = start_rule(&g, &options);
+        let mut coverage = Coverage::default();
+        let passed = match run_corpus(&g, &start, corpus, *earley, &mut coverage) {
+            Ok(passed) => passed,
+            Err(message) => {
+                eprintln!("{}", message);
+                ExitCode::ErrorMatching.exit()
+            }
+        };
+        if *print_coverage {
+            print!("{}", format_coverage(&g, &coverage));
+        }
+        if let Some(path) = coverage_html {
+            let html = format_html(
+                &g,
+                &content,
+                &options.section_header,
+                options.section_id,
+                options.railroad,
+                Some(&coverage),
+                options.toc,
+            );
+            if let Err(error) = std::fs::write(path, html) {
+                eprintln!("unable to write {}: {}", path.display(), error);
+                ExitCode::ErrorMatching.exit()
+            }
+        }
+        if !passed {
+            ExitCode::ErrorMatching.exit()
+        }
+        ExitCode::Success.exit()
+    }
+
+    if let Command::Fuzz {
+        options: generate,
+        count,
+    } = &options.command
+    {
+        fuzz(&g, &options, generate, *count)
+    }
+
+    if options.command == Command::Analyze {
+        print!("{}", format_analysis(&g, &start_rule(&g, &options)));
+        ExitCode::Success.exit()
+    }
+
+    if let Some(dir) = &options.railroad_dir {
+        std::fs::create_dir_all(dir).expect("Unable to create directory");
+        for (id, svg) in format_railroad(&g) {
+            let path = dir.join(format!("{}.svg", id));
+            std::fs::write(path, svg).expect("Unable to write file");
+        }
+    }
+
+    let output = match options.format {
+        Format::Html => format_html(
+            &g,
+            &content,
+            &options.section_header,
+            options.section_id,
+            options.railroad,
+            None,
+            options.toc,
+        ),
+        Format::Markdown => format_markdown(&g),
+        #[cfg(feature = "serde")]
+        Format::Json => format_json(&g),
+    };
+    println!("{}", output);
+    ExitCode::Success.exit()
+}
+
+fn parse_file(
+    g: &Grammar,
+    options: &CliOptions,
+    file: &Option<PathBuf>,
+    earley: bool,
+    tree: &Option<TreeFormat>,
+) -> ! {
+    let input = match file {
+        None => {
+            let mut s = String::new();
+            io::stdin().read_to_string(&mut s).unwrap();
+            s
+        }
+        Some(file) => std::fs::read_to_string(file).expect("Unable to read file"),
+    };
+    let start = start_rule(g, options);
+    let result = if earley {
+        g.recognize(&start, &input).map(|derivations| {
+            if derivations == Derivations::Many {
+                eprintln!("warning: the input has more than one derivation");
+            }
+        })
+    } else {
+        g.interpret(&start, &input).map(|node| match tree {
+            None => {}
+            Some(TreeFormat::Text) => print!("{}", node.to_text(&input)),
+            Some(TreeFormat::Sexp) => println!("{}", node.to_sexp(&input)),
+            Some(TreeFormat::Json) => println!("{}", node.to_json(&input)),
+        })
+    };
+    if let Err(error) = result {
+        let pos = find_position(&input, error.span.start);
+        eprintln!("{}:{}: {}", pos.line, pos.column, error.message);
+        ExitCode::ErrorMatching.exit()
+    }
+    ExitCode::Success.exit()
+}
+
+fn fuzz(g: &Grammar, options: &CliOptions, generate: &GenerateOptions, count: usize) -> ! {
+    let start = start_rule(g, options);
+    for i in 0..count {
+        let generate = GenerateOptions {
+            seed: generate.seed.wrapping_add(i as u64),
+            ..generate.clone()
+        };
+        if let Some(s) = g.generate(&start, &generate) {
+            println!("{}", s);
         }
+    }
+    ExitCode::Success.exit()
+}
+
+// the rule given with --start, or the first rule
+fn start_rule(g: &Grammar, options: &CliOptions) -> String {
+    match &options.start {
+        None => match g.get_rules().first() {
+            Some(rule) => rule.id.clone(),
+            None => {
+                eprintln!("the grammar has no rules");
+                ExitCode::ErrorValidation.exit()
+            }
+        },
+        Some(start) => start.clone(),
+    }
+}
+
+fn check_start(g: &Grammar, start: &str) {
+    if !g.get_rules().iter().any(|rule| rule.id == start) {
+        eprintln!("start rule <{}> is not defined", start);
         ExitCode::ErrorValidation.exit()
     }
+}
 
-    println!(
-        "{}",
-        format_html(&g, &content, &options.section_header, options.section_id)
-    );
+fn fmt(g: &Grammar, content: &str, options: &CliOptions, check: bool) -> ! {
+    let formatted = format_source(g);
+    if !check {
+        print!("{}", formatted);
+    } else if formatted != content {
+        let name = match &options.input_file {
+            None => "<stdin>".to_string(),
+            Some(input_file) => input_file.display().to_string(),
+        };
+        eprintln!("{} is not formatted", name);
+        ExitCode::ErrorFormatting.exit()
+    }
     ExitCode::Success.exit()
 }
 
 pub fn find_position(s: &str, offset: usize) -> Pos {
-    debug_assert!(offset < s.len());
+    debug_assert!(offset <= s.len());
     let positions = Pos::all(s);
 
     positions
@@ -1,13 +1,73 @@
+use grammar::GenerateOptions;
 use std::path::PathBuf;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CliOptions {
+    pub command: Command,
     pub verbose: bool,
     pub input_file: Option<PathBuf>,
     pub section_header: String,
     pub section_id: bool,
+    pub toc: bool,
+    pub format: Format,
+    pub railroad: bool,
+    pub railroad_dir: Option<PathBuf>,
+    pub ll1: bool,
+    pub start: Option<String>,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    // validate the grammar and generate its representation
+    Generate,
+    // print the grammar with a canonical layout
+    Fmt {
+        check: bool,
+    },
+    // print the nullable, FIRST and FOLLOW sets of every rule
+    Analyze,
+    // validate the grammar and match the examples of its rules
+    Check,
+    // match an input file against the grammar
+    Parse {
+        file: Option<PathBuf>,
+        earley: bool,
+        tree: Option<TreeFormat>,
+    },
+    // match the files of a corpus directory (ok/ and err/)
+    Test {
+        corpus: PathBuf,
+        earley: bool,
+        coverage: bool,
+        coverage_html: Option<PathBuf>,
+    },
+    // print random strings derived from the grammar
+    Fuzz {
+        options: GenerateOptions,
+        count: usize,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TreeFormat {
+    Text,
+    Sexp,
+    Json,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+    Html,
+    Markdown,
+    #[cfg(feature = "serde")]
+    Json,
+}
+
+#[cfg(feature = "serde")]
+const FORMATS: [&str; 3] = ["html", "markdown", "json"];
+#[cfg(not(feature = "serde"))]
+const FORMATS: [&str; 2] = ["html", "markdown"];
+
 // clap (unfortunately) panics when options are not good
 // for consistency, you should exit in case of errors.
 // But I would have prefer the standard Result return type!
@@ -16,11 +76,7 @@ pub fn parse_options() -> CliOptions {
         .version(clap::crate_version!())
         .author(clap::crate_authors!())
         .about(clap::crate_description!())
-        .arg(
-            clap::Arg::new("INPUT")
-                .help("Sets the input file to use")
-                .required(false),
-        )
+        .arg(input_arg())
         .arg(
             clap::Arg::new("section_id")
                 .long("section-id")
@@ -33,23 +89,216 @@ pub fn parse_options() -> CliOptions {
                 .default_value("h2")
                 .help("Specify section header"),
         )
+        .arg(
+            clap::Arg::new("toc")
+                .long("toc")
+                .help("Add a table of contents to the HTML output"),
+        )
+        .arg(
+            clap::Arg::new("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(FORMATS)
+                .default_value("html")
+                .help("Specify output format"),
+        )
+        .arg(
+            clap::Arg::new("railroad")
+                .long("railroad")
+                .help("Add railroad diagrams to the HTML output"),
+        )
+        .arg(
+            clap::Arg::new("railroad_dir")
+                .long("railroad-dir")
+                .takes_value(true)
+                .value_name("DIR")
+                .help("Write a railroad diagram SVG file for each rule in DIR"),
+        )
+        .arg(
+            clap::Arg::new("ll1")
+                .long("ll1")
+                .help("Report LL(1) conflicts as validation errors"),
+        )
+        .arg(start_arg())
         .arg(
             clap::Arg::new("verbose")
                 .long("verbose")
                 .help("Turn verbose mode"),
+        )
+        .subcommand(
+            clap::Command::new("fmt")
+                .about("Print the grammar file with a canonical layout")
+                .arg(input_arg())
+                .arg(
+                    clap::Arg::new("check")
+                        .long("check")
+                        .help("Exit with an error if the file is not formatted"),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("analyze")
+                .about("Print the nullable, FIRST and FOLLOW sets of every rule")
+                .arg(start_arg())
+                .arg(input_arg()),
+        )
+        .subcommand(
+            clap::Command::new("check")
+                .about("Validate the grammar and match the examples of its rules")
+                .arg(start_arg())
+                .arg(input_arg()),
+        )
+        .subcommand(
+            clap::Command::new("parse")
+                .about("Match an input file against the grammar")
+                .arg(start_arg())
+                .arg(earley_arg())
+                .arg(
+                    clap::Arg::new("tree")
+                        .long("tree")
+                        .takes_value(true)
+                        .value_name("FORMAT")
+                        .possible_values(["text", "sexp", "json"])
+                        .conflicts_with("earley")
+                        .help("Print the parse tree"),
+                )
+                .arg(input_arg().required(true))
+                .arg(
+                    clap::Arg::new("INPUT")
+                        .help("Sets the input file to match (standard input by default)")
+                        .required(false),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("test")
+                .about("Match the files of a corpus: ok/ files must match, err/ files must not")
+                .arg(start_arg())
+                .arg(earley_arg())
+                .arg(
+                    clap::Arg::new("coverage")
+                        .long("coverage")
+                        .conflicts_with("earley")
+                        .help("Print the rules and alternatives not used by the corpus"),
+                )
+                .arg(
+                    clap::Arg::new("coverage_html")
+                        .long("coverage-html")
+                        .takes_value(true)
+                        .value_name("OUT")
+                        .conflicts_with("earley")
+                        .help("Write the HTML grammar with the unused parts shaded to OUT"),
+                )
+                .arg(input_arg().required(true))
+                .arg(
+                    clap::Arg::new("CORPUS")
+                        .help("Sets the corpus directory, containing ok/ and err/ directories")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("fuzz")
+                .about("Print random strings derived from the grammar")
+                .arg(start_arg())
+                .arg(number_arg(
+                    "seed",
+                    "seed",
+                    "Seed of the random generator (from the time by default)",
+                ))
+                .arg(number_arg(
+                    "max_depth",
+                    "max-depth",
+                    "Nesting of rules beyond which the shortest alternatives are chosen [default: 16]",
+                ))
+                .arg(number_arg(
+                    "min_repeat",
+                    "min-repeat",
+                    "Minimum number of repetitions for * and + [default: 0]",
+                ))
+                .arg(number_arg(
+                    "max_repeat",
+                    "max-repeat",
+                    "Maximum number of repetitions for * and + [default: 3]",
+                ))
+                .arg(number_arg(
+                    "count",
+                    "count",
+                    "Number of strings, one per line [default: 1]",
+                ))
+                .arg(input_arg()),
         );
     let matches = command.clone().get_matches();
 
-    let input_file = match matches.value_of("INPUT") {
-        None => None,
-        Some(s) => {
-            let path = std::path::Path::new(s);
-            if !path.exists() {
-                eprintln!("The input file {} does not exist", path.display());
+    let mut start = matches.value_of("start").map(|s| s.to_string());
+    let (command_value, input_file) = match matches.subcommand() {
+        Some(("fmt", sub_matches)) => {
+            let check = sub_matches.is_present("check");
+            (Command::Fmt { check }, input_file(sub_matches))
+        }
+        Some(("analyze", sub_matches)) => {
+            start = start_value(sub_matches, start);
+            (Command::Analyze, input_file(sub_matches))
+        }
+        Some(("check", sub_matches)) => {
+            start = start_value(sub_matches, start);
+            (Command::Check, input_file(sub_matches))
+        }
+        Some(("parse", sub_matches)) => {
+            start = start_value(sub_matches, start);
+            let file = sub_matches.value_of("INPUT").map(PathBuf::from);
+            let earley = sub_matches.is_present("earley");
+            let tree = match sub_matches.value_of("tree") {
+                None => None,
+                Some("sexp") => Some(TreeFormat::Sexp),
+                Some("json") => Some(TreeFormat::Json),
+                _ => Some(TreeFormat::Text),
+            };
+            (
+                Command::Parse { file, earley, tree },
+                input_file(sub_matches),
+            )
+        }
+        Some(("test", sub_matches)) => {
+            start = start_value(sub_matches, start);
+            let corpus = PathBuf::from(sub_matches.value_of("CORPUS").unwrap_or_default());
+            if !corpus.is_dir() {
+                eprintln!("The corpus directory {} does not exist", corpus.display());
                 std::process::exit(2);
             }
-            Some(path.to_path_buf())
+            let earley = sub_matches.is_present("earley");
+            let coverage = sub_matches.is_present("coverage");
+            let coverage_html = sub_matches.value_of("coverage_html").map(PathBuf::from);
+            (
+                Command::Test {
+                    corpus,
+                    earley,
+                    coverage,
+                    coverage_html,
+                },
+                input_file(sub_matches),
+            )
         }
+        Some(("fuzz", sub_matches)) => {
+            start = start_value(sub_matches, start);
+            let default = GenerateOptions::default();
+            let seed = match number_value(sub_matches, "seed") {
+                Some(seed) => seed,
+                None => std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|duration| duration.as_nanos() as u64)
+                    .unwrap_or_default(),
+            };
+            let value = |name: &str, default: usize| {
+                number_value(sub_matches, name).map_or(default, |value| value as usize)
+            };
+            let options = GenerateOptions {
+                seed,
+                max_depth: value("max_depth", default.max_depth),
+                min_repeat: value("min_repeat", default.min_repeat),
+                max_repeat: value("max_repeat", default.max_repeat),
+            };
+            let count = value("count", 1);
+            (Command::Fuzz { options, count }, input_file(sub_matches))
+        }
+        _ => (Command::Generate, input_file(&matches)),
     };
 
     let verbose = matches.is_present("verbose");
@@ -58,15 +307,99 @@ pub fn parse_options() -> CliOptions {
         .unwrap_or_default()
         .to_string();
     let section_id = matches.is_present("section_id");
+    let toc = matches.is_present("toc");
+    let format = match matches.value_of("format") {
+        Some("markdown") => Format::Markdown,
+        #[cfg(feature = "serde")]
+        Some("json") => Format::Json,
+        _ => Format::Html,
+    };
+    let railroad = matches.is_present("railroad");
+    let railroad_dir = matches.value_of("railroad_dir").map(PathBuf::from);
+    let ll1 = matches.is_present("ll1");
     if input_file.is_none() && atty::is(atty::Stream::Stdin) {
         command.clone().print_help().unwrap();
         std::process::exit(2);
     }
 
     CliOptions {
+        command: command_value,
         input_file,
         verbose,
         section_header,
         section_id,
+        toc,
+        format,
+        railroad,
+        railroad_dir,
+        ll1,
+        start,
+    }
+}
+
+// the start rule given to the subcommand, or else to the command
+fn start_value(sub_matches: &clap::ArgMatches, start: Option<String>) -> Option<String> {
+    sub_matches
+        .value_of("start")
+        .map(|s| s.to_string())
+        .or(start)
+}
+
+fn start_arg() -> clap::Arg<'static> {
+    clap::Arg::new("start")
+        .long("start")
+        .takes_value(true)
+        .value_name("RULE")
+        .help("Specify the start rule (the first rule by default)")
+}
+
+fn earley_arg() -> clap::Arg<'static> {
+    clap::Arg::new("earley")
+        .long("earley")
+        .help("Use unordered choices and report ambiguous inputs")
+}
+
+fn number_arg(name: &'static str, long: &'static str, help: &'static str) -> clap::Arg<'static> {
+    clap::Arg::new(name)
+        .long(long)
+        .takes_value(true)
+        .value_name("N")
+        .help(help)
+}
+
+fn number_value(matches: &clap::ArgMatches, name: &str) -> Option<u64> {
+    match matches.value_of(name) {
+        None => None,
+        Some(s) => match s.parse() {
+            Ok(value) => Some(value),
+            Err(_) => {
+                eprintln!(
+                    "The value {} of --{} is not a number",
+                    s,
+                    name.replace('_', "-")
+                );
+                std::process::exit(2);
+            }
+        },
+    }
+}
+
+fn input_arg() -> clap::Arg<'static> {
+    clap::Arg::new("FILE")
+        .help("Sets the grammar file to use")
+        .required(false)
+}
+
+fn input_file(matches: &clap::ArgMatches) -> Option<PathBuf> {
+    match matches.value_of("FILE") {
+        None => None,
+        Some(s) => {
+            let path = std::path::Path::new(s);
+            if !path.exists() {
+                eprintln!("The input file {} does not exist", path.display());
+                std::process::exit(2);
+            }
+            Some(path.to_path_buf())
+        }
     }
 }
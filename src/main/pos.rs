@@ -5,6 +5,7 @@ pub struct Pos {
 }
 
 impl Pos {
+    // the last position is the end of the string
     #[allow(dead_code)]
     pub fn all(s: &str) -> Vec<Pos> {
         let chars = s.chars().collect::<Vec<char>>();
@@ -22,6 +23,7 @@ impl Pos {
                 column += 1;
             }
         }
+        positions.push(Pos { line, column });
         positions
     }
 }
@@ -1,9 +1,13 @@
 mod parser;
 mod scanner;
+mod source_map;
 mod tokens;
 
+#[cfg(test)]
+pub(crate) use self::parser::character_class;
 pub use self::parser::Parser;
 pub use self::scanner::Scanner;
+pub use self::source_map::{Location, SourceError, SourceMap};
 pub use self::tokens::{Element, Node, NodeType, ParseError, Token, TokenKind};
 use crate::core::Grammar;
 
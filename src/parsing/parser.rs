@@ -82,6 +82,7 @@ impl Parser {
 
     pub fn grammar_file(&mut self) -> Result<Grammar, Vec<ParseError>> {
         let mut rulesets = vec![];
+        let mut includes = vec![];
         let mut errors = vec![];
         loop {
             // consume newline
@@ -91,13 +92,32 @@ impl Parser {
                 break;
             }
 
+            match self.include() {
+                Ok(None) => {}
+                Ok(Some(include)) => {
+                    includes.push(include);
+                    continue;
+                }
+                Err(error) => {
+                    errors.push(error);
+                    self.synchronize();
+                    continue;
+                }
+            }
+
             match self.rule_set() {
                 Ok(None) => {
                     let span = Span {
                         start: self.offset(),
                         end: self.offset(),
                     };
-                    let message = format!("Unexpected character {:?}", self.peek());
+                    let message = match self.peek() {
+                        Some(Token {
+                            kind: TokenKind::Error(message),
+                            ..
+                        }) => message,
+                        token => format!("Unexpected character {:?}", token),
+                    };
                     let error = ParseError { span, message };
                     errors.push(error);
                     self.synchronize();
@@ -111,18 +131,41 @@ impl Parser {
         }
 
         if errors.is_empty() {
-            Ok(Grammar { rulesets })
+            Ok(Grammar { rulesets, includes })
         } else {
             Err(errors)
         }
     }
 
+    // include directive, before or between the sections
+    fn include(&mut self) -> Result<Option<Include>, ParseError> {
+        let start = self.offset();
+        if self.match_token(TokenKind::Include).is_none() {
+            return Ok(None);
+        }
+        match self.peek() {
+            Some(Token {
+                kind: TokenKind::LiteralString(path),
+                span,
+            }) => {
+                self.next();
+                let span = Span {
+                    start,
+                    end: span.end,
+                };
+                Ok(Some(Include { span, path }))
+            }
+            _ => Err(self.parse_error("Expecting a file path")),
+        }
+    }
+
     fn rule_set(&mut self) -> Result<Option<RuleSet>, Vec<ParseError>> {
         let comment = if let Some(comment) = self.comment() {
             comment
         } else {
             return Ok(None);
         };
+        let prose = self.prose();
         let mut rules = vec![];
         let mut errors = vec![];
         loop {
@@ -150,7 +193,11 @@ impl Parser {
             }
         }
         if errors.is_empty() {
-            Ok(Some(RuleSet { comment, rules }))
+            Ok(Some(RuleSet {
+                comment,
+                prose,
+                rules,
+            }))
         } else {
             Err(errors)
         }
@@ -166,18 +213,38 @@ impl Parser {
         }) = self.peek()
         {
             self.next();
-            let value = value.trim().to_string();
-            Some(Comment { span, value })
+            let level = 1 + value.chars().take_while(|c| *c == '#').count();
+            let value = value.trim_start_matches('#').trim().to_string();
+            Some(Comment { span, value, level })
+        } else {
+            None
+        }
+    }
+
+    // prose block following the section heading
+    fn prose(&mut self) -> Option<Prose> {
+        let current = self.current;
+        self.skip_newlines();
+        if let Some(Token {
+            kind: TokenKind::Prose(value),
+            span,
+        }) = self.peek()
+        {
+            self.next();
+            Some(Prose { span, value })
         } else {
+            self.current = current;
             None
         }
     }
 
     fn rule(&mut self) -> Result<Option<Rule>, ParseError> {
+        let doc = self.doc();
         let start = self.offset();
         let id = match self.identifier_expression() {
             Some(value) => value,
-            None => return Ok(None),
+            None if doc.is_empty() => return Ok(None),
+            None => return Err(self.parse_error("Expecting a rule after a doc comment")),
         };
         if self.match_token(TokenKind::Colon).is_none() {
             return Err(ParseError {
@@ -203,13 +270,60 @@ impl Parser {
 
         let end = self.offset();
         let span = Span { start, end };
+        let examples = self.examples();
         Ok(Some(Rule {
             span,
             id,
             expression,
+            examples,
+            doc,
         }))
     }
 
+    // doc comments preceding a rule, one per line
+    fn doc(&mut self) -> Vec<DocComment> {
+        let mut doc = vec![];
+        while let Some(Token {
+            kind: TokenKind::Doc(value),
+            span,
+        }) = self.peek()
+        {
+            self.next();
+            doc.push(DocComment { span, value });
+            self.skip_newlines();
+        }
+        doc
+    }
+
+    // examples following a rule, one per line
+    fn examples(&mut self) -> Vec<Example> {
+        let mut examples = vec![];
+        loop {
+            let mut current = self.current;
+            while let Some(Token {
+                kind: TokenKind::Newline(_),
+                ..
+            }) = self.tokens.get(current)
+            {
+                current += 1;
+            }
+            match self.tokens.get(current) {
+                Some(Token {
+                    kind: TokenKind::Example(value),
+                    span,
+                }) => {
+                    examples.push(Example {
+                        span: span.clone(),
+                        value: value.clone(),
+                    });
+                    self.current = current + 1;
+                }
+                _ => break,
+            }
+        }
+        examples
+    }
+
     fn choice_expression(&mut self) -> Result<Option<Expression>, ParseError> {
         let start = self.offset();
         if self.is_eof() {
@@ -297,6 +411,14 @@ impl Parser {
         if self.is_eof() {
             return Ok(None);
         }
+        // errors of the scanner, such as an invalid escape sequence
+        if let Some(Token {
+            kind: TokenKind::Error(message),
+            span,
+        }) = self.peek()
+        {
+            return Err(ParseError { span, message });
+        }
         if self.match_token(TokenKind::LeftParenthesis).is_none() {
             return Ok(self.primary_expression());
         }
@@ -385,7 +507,9 @@ impl Parser {
             let kind = match kind {
                 TokenKind::Identifier(value) => ExpressionKind::NonTerminal(value),
                 TokenKind::LiteralString(value) => ExpressionKind::Literal(value),
-                TokenKind::Regex(value) => ExpressionKind::Regex(value),
+                TokenKind::Regex(value) => {
+                    ExpressionKind::Regex(character_class(&value, span.start))
+                }
                 _ => return None,
             };
             self.next();
@@ -409,6 +533,68 @@ impl Parser {
     }
 }
 
+// builds a character class from the text scanned (for instance `~[a-z\]]+`),
+// the spans of its items starting at the offset of the text
+pub(crate) fn character_class(text: &str, offset: usize) -> CharacterClass {
+    let chars = text.chars().collect::<Vec<char>>();
+    let negated = chars.first() == Some(&'~');
+    let mut i = if negated { 2 } else { 1 };
+
+    // a char of the class and the offset following it, None for the closing bracket
+    let class_char = |i: usize| match chars.get(i) {
+        None | Some(']') => None,
+        Some('\\') => chars.get(i + 1).map(|c| (ClassChar::Escape(*c), i + 2)),
+        Some(c) => Some((ClassChar::Char(*c), i + 1)),
+    };
+
+    let mut items = vec![];
+    while let Some((c, next)) = class_char(i) {
+        let start = i;
+        let kind = match (chars.get(next), class_char(next + 1)) {
+            (Some('-'), Some((end, next))) => {
+                i = next;
+                ClassItemKind::Range(c, end)
+            }
+            _ => {
+                i = next;
+                ClassItemKind::Single(c)
+            }
+        };
+        items.push(ClassItem {
+            span: Span {
+                start: offset + start,
+                end: offset + i,
+            },
+            kind,
+        });
+    }
+
+    let quantifier = match chars.get(i + 1) {
+        Some('?') => Some(Quantifier::ZeroOrOne),
+        Some('+') => Some(Quantifier::OneOrMany),
+        Some('*') => Some(Quantifier::Many),
+        // checked by the scanner
+        Some('{') => {
+            let bounds = chars[i + 2..chars.len() - 1].iter().collect::<String>();
+            let (min, max) = match bounds.split_once(',') {
+                None => (bounds.as_str(), Some(bounds.as_str())),
+                Some((min, "")) => (min, None),
+                Some((min, max)) => (min, Some(max)),
+            };
+            Some(Quantifier::Repeat {
+                min: min.parse().unwrap_or_default(),
+                max: max.map(|max| max.parse().unwrap_or_default()),
+            })
+        }
+        _ => None,
+    };
+    CharacterClass {
+        negated,
+        items,
+        quantifier,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -429,6 +615,28 @@ mod tests {
         eprintln!("{:#?}", grammar_file);
     }
 
+    #[test]
+    pub fn test_include() {
+        let mut parser = Parser::init(vec![
+            Token {
+                kind: TokenKind::Include,
+                span: Span { start: 0, end: 8 },
+            },
+            literal_string_token(9, "a.grammar"),
+            newline_token(20, "\n"),
+            comment_token(21, " Comment"),
+        ]);
+        let grammar = parser.grammar_file().unwrap();
+        assert_eq!(
+            grammar.includes,
+            vec![Include {
+                span: Span { start: 0, end: 18 },
+                path: "a.grammar".to_string(),
+            }]
+        );
+        assert_eq!(grammar.rulesets.len(), 1);
+    }
+
     // #[test]
     // pub fn test_choice() {
     //     let mut parser = Parser::init(vec![
@@ -469,10 +677,70 @@ mod tests {
                     span: Span { start: 16, end: 19 },
                     kind: ExpressionKind::NonTerminal("aaa".to_string()),
                 },
+                examples: vec![],
+                doc: vec![],
             }
         );
     }
 
+    #[test]
+    pub fn test_rule_examples() {
+        let mut parser = Parser::init(vec![
+            identifier_token(0, "a"),
+            colon_token(1),
+            identifier_token(3, "b"),
+            newline_token(4, "\n"),
+            Token {
+                kind: TokenKind::Example("x".to_string()),
+                span: Span { start: 5, end: 9 },
+            },
+            newline_token(9, "\n"),
+            identifier_token(10, "b"),
+        ]);
+        let rule = parser.rule().unwrap().unwrap();
+        assert_eq!(
+            rule.examples,
+            vec![Example {
+                span: Span { start: 5, end: 9 },
+                value: "x".to_string(),
+            }]
+        );
+        assert_eq!(rule.span, Span { start: 0, end: 4 });
+        assert_eq!(parser.current, 5);
+    }
+
+    #[test]
+    pub fn test_rule_doc() {
+        let mut parser = Parser::init(vec![
+            Token {
+                kind: TokenKind::Doc("A rule".to_string()),
+                span: Span { start: 0, end: 8 },
+            },
+            newline_token(8, "\n"),
+            identifier_token(9, "a"),
+            colon_token(10),
+            identifier_token(12, "b"),
+        ]);
+        let rule = parser.rule().unwrap().unwrap();
+        assert_eq!(
+            rule.doc,
+            vec![DocComment {
+                span: Span { start: 0, end: 8 },
+                value: "A rule".to_string(),
+            }]
+        );
+        assert_eq!(rule.span, Span { start: 9, end: 13 });
+
+        let mut parser = Parser::init(vec![Token {
+            kind: TokenKind::Doc("A rule".to_string()),
+            span: Span { start: 0, end: 8 },
+        }]);
+        assert_eq!(
+            parser.rule().unwrap_err().message,
+            "Expecting a rule after a doc comment"
+        );
+    }
+
     #[test]
     pub fn test_choice_expression() {
         let mut parser = Parser::init(vec![identifier_token(10, "item")]);
@@ -610,6 +878,50 @@ mod tests {
         assert_eq!(parser.current, 1);
     }
 
+    #[test]
+    pub fn test_character_class() {
+        let class = character_class("~[a-z\\]-]+", 10);
+        assert_eq!(
+            class,
+            CharacterClass {
+                negated: true,
+                items: vec![
+                    ClassItem {
+                        span: Span { start: 12, end: 15 },
+                        kind: ClassItemKind::Range(ClassChar::Char('a'), ClassChar::Char('z')),
+                    },
+                    ClassItem {
+                        span: Span { start: 15, end: 17 },
+                        kind: ClassItemKind::Single(ClassChar::Escape(']')),
+                    },
+                    ClassItem {
+                        span: Span { start: 17, end: 18 },
+                        kind: ClassItemKind::Single(ClassChar::Char('-')),
+                    },
+                ],
+                quantifier: Some(Quantifier::OneOrMany),
+            }
+        );
+        assert_eq!(class.to_string(), "~[a-z\\]-]+");
+
+        let class = character_class("[\\t- ]", 0);
+        assert_eq!(
+            class.items,
+            vec![ClassItem {
+                span: Span { start: 1, end: 5 },
+                kind: ClassItemKind::Range(ClassChar::Escape('t'), ClassChar::Char(' ')),
+            }]
+        );
+        assert_eq!(class.quantifier, None);
+
+        let class = character_class("[a]{2,}", 0);
+        assert_eq!(
+            class.quantifier,
+            Some(Quantifier::Repeat { min: 2, max: None })
+        );
+        assert_eq!(class.to_string(), "[a]{2,}");
+    }
+
     //
     // Helpers
     //
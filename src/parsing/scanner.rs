@@ -31,6 +31,7 @@ impl Iterator for Scanner {
         if let Some(c) = self.read() {
             let token = match c {
                 '~' => self.tilde(),
+                '@' => self.directive(),
                 ':' => Token {
                     kind: TokenKind::Colon,
                     span: Span {
@@ -80,7 +81,35 @@ impl Iterator for Scanner {
                         end: start + 1,
                     },
                 },
+                '{' => match self.bounded_quantifier() {
+                    Ok(quantifier) => Token {
+                        kind: TokenKind::Quantifier(quantifier),
+                        span: Span {
+                            start,
+                            end: self.offset,
+                        },
+                    },
+                    Err(token) => token,
+                },
                 '#' => self.comment(),
+                '"' if self.peek() == Some('"')
+                    && self.buffer.get(self.offset + 1) == Some(&'"')
+                    && self.starts_line(start) =>
+                {
+                    let follows_heading = self.follows_heading(start);
+                    match self.prose() {
+                        Token {
+                            kind: TokenKind::Prose(_),
+                            span,
+                        } if !follows_heading => Token {
+                            kind: TokenKind::Error(
+                                "A prose block must follow a section heading".to_string(),
+                            ),
+                            span,
+                        },
+                        token => token,
+                    }
+                }
                 '"' => self.literal_string(),
                 '[' => self.character_class(false),
                 _ => {
@@ -122,6 +151,26 @@ impl Scanner {
         }
     }
 
+    fn directive(&mut self) -> Token {
+        let start = self.offset - 1;
+        while let Some(c) = self.peek() {
+            if !c.is_alphanumeric() {
+                break;
+            }
+            self.read();
+        }
+        let text: String = self.buffer[start..self.offset].iter().collect();
+        let end = self.offset;
+        let kind = match text.as_str() {
+            "@include" => TokenKind::Include,
+            _ => TokenKind::Error(format!("Unknown directive '{}'", text)),
+        };
+        Token {
+            kind,
+            span: Span { start, end },
+        }
+    }
+
     fn whitespace(&mut self) -> Token {
         let start = self.offset - 1;
 
@@ -149,6 +198,8 @@ impl Scanner {
     fn literal_string(&mut self) -> Token {
         let start = self.offset - 1;
         let mut text = "".to_string();
+        // the first invalid escape sequence, reported once the string is read
+        let mut error = None;
         loop {
             match self.read() {
                 None => {
@@ -170,29 +221,13 @@ impl Scanner {
                 Some('"') => {
                     break;
                 }
-                Some('\\') => match self.read() {
-                    Some('"') => {
-                        text.push('"');
-                    }
-                    // Some('n') => {
-                    //     text.push('\\');
-                    //     text.push('n');
-                    // }
-                    // Some('r') => {
-                    //     text.push('\\');
-                    //     text.push('r');
-                    // }
-                    // Some('t') => {
-                    //     text.push('\\');
-                    //     text.push('t');
-                    // }
-                    Some('\\') => {
-                        text.push('\\');
-                    }
-                    Some(c) => {
-                        text.push('\\');
-                        text.push(c);
-                    }
+                Some('\\') => match self.peek() {
+                    Some(_) => match self.escape() {
+                        Ok(c) => text.push(c),
+                        Err(token) => {
+                            error.get_or_insert(token);
+                        }
+                    },
                     None => {
                         let offset = self.offset;
                         let text = "Unexpected End of file".to_string();
@@ -208,6 +243,9 @@ impl Scanner {
                 Some(c) => text.push(c),
             }
         }
+        if let Some(token) = error {
+            return token;
+        }
         let end = self.offset;
         Token {
             kind: TokenKind::LiteralString(text.to_string()),
@@ -215,7 +253,136 @@ impl Scanner {
         }
     }
 
+    // whether the offset is preceded by whitespace only on its line
+    fn starts_line(&self, offset: usize) -> bool {
+        self.buffer[..offset]
+            .iter()
+            .rev()
+            .take_while(|c| **c != '\n')
+            .all(|c| c.is_whitespace())
+    }
+
+    // whether the previous non-empty line of the offset is a section heading
+    // (and not a doc comment or an example)
+    fn follows_heading(&self, offset: usize) -> bool {
+        let end = match self.buffer[..offset]
+            .iter()
+            .rposition(|c| !c.is_whitespace())
+        {
+            None => return false,
+            Some(i) => i + 1,
+        };
+        let start = self.buffer[..end]
+            .iter()
+            .rposition(|c| *c == '\n')
+            .map_or(0, |i| i + 1);
+        let line = self.buffer[start..end].iter().collect::<String>();
+        let line = line.trim_start();
+        line.starts_with('#') && !line.starts_with("#:") && !line.starts_with("#>")
+    }
+
+    // prose block between two lines of triple quotes, following a section heading
+    // the newlines following the opening quotes and preceding the closing ones are not kept
+    fn prose(&mut self) -> Token {
+        let start = self.offset - 1;
+        self.offset += 2;
+        let mut text = "".to_string();
+        loop {
+            if self.buffer[self.offset..].starts_with(&['"', '"', '"']) {
+                self.offset += 3;
+                break;
+            }
+            match self.read() {
+                None => {
+                    return Token {
+                        kind: TokenKind::Error("Expected a closing '\"\"\"'".to_string()),
+                        span: Span { start, end: start },
+                    };
+                }
+                Some(c) => text.push(c),
+            }
+        }
+        let end = self.offset;
+        let text = text.trim_start_matches([' ', '\t']);
+        let text = text.strip_prefix('\n').unwrap_or(text);
+        Token {
+            kind: TokenKind::Prose(text.trim_end().to_string()),
+            span: Span { start, end },
+        }
+    }
+
+    // decodes the escape sequence following a backslash in a literal string:
+    // \" \\ \n \r \t \0 \xHH or \u{HHHH} (one to six hexadecimal digits)
+    fn escape(&mut self) -> Result<char, Token> {
+        let start = self.offset - 1;
+        let c = match self.peek() {
+            Some('\n') | None => None,
+            Some(c) => {
+                self.offset += 1;
+                match c {
+                    '"' => Some('"'),
+                    '\\' => Some('\\'),
+                    'n' => Some('\n'),
+                    'r' => Some('\r'),
+                    't' => Some('\t'),
+                    '0' => Some('\0'),
+                    'x' => match self.hex_digits(2) {
+                        digits if digits.len() == 2 => u32::from_str_radix(&digits, 16)
+                            .ok()
+                            .and_then(char::from_u32),
+                        _ => None,
+                    },
+                    'u' if self.peek() == Some('{') => {
+                        self.offset += 1;
+                        let digits = self.hex_digits(6);
+                        match (digits.len(), self.peek()) {
+                            (1..=6, Some('}')) => {
+                                self.offset += 1;
+                                u32::from_str_radix(&digits, 16)
+                                    .ok()
+                                    .and_then(char::from_u32)
+                            }
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                }
+            }
+        };
+        c.ok_or_else(|| {
+            let sequence = self.buffer[start..self.offset].iter().collect::<String>();
+            Token {
+                kind: TokenKind::Error(format!("Invalid escape sequence '{}'", sequence)),
+                span: Span {
+                    start,
+                    end: self.offset,
+                },
+            }
+        })
+    }
+
+    // reads at most `max` hexadecimal digits
+    fn hex_digits(&mut self, max: usize) -> String {
+        let mut digits = "".to_string();
+        while digits.len() < max {
+            match self.peek() {
+                Some(c) if c.is_ascii_hexdigit() => {
+                    self.offset += 1;
+                    digits.push(c);
+                }
+                _ => break,
+            }
+        }
+        digits
+    }
+
     fn comment(&mut self) -> Token {
+        if self.peek() == Some('>') {
+            return self.example();
+        }
+        if self.peek() == Some(':') {
+            return self.doc();
+        }
         let start = self.offset - 1;
         let mut text = "".to_string();
         loop {
@@ -236,6 +403,45 @@ impl Scanner {
         }
     }
 
+    // example of the previous rule: "#>" followed by an optional space
+    fn example(&mut self) -> Token {
+        let start = self.offset - 1;
+        let text = self.line_after_marker();
+        let end = self.offset;
+        Token {
+            kind: TokenKind::Example(text),
+            span: Span { start, end },
+        }
+    }
+
+    // doc comment of the next rule: "#:" followed by an optional space
+    fn doc(&mut self) -> Token {
+        let start = self.offset - 1;
+        let text = self.line_after_marker();
+        let end = self.offset;
+        Token {
+            kind: TokenKind::Doc(text),
+            span: Span { start, end },
+        }
+    }
+
+    // rest of the line after a two-char marker and an optional space
+    fn line_after_marker(&mut self) -> String {
+        self.read();
+        if self.peek() == Some(' ') {
+            self.read();
+        }
+        let mut text = "".to_string();
+        while let Some(c) = self.peek() {
+            if c == '\n' {
+                break;
+            }
+            self.read();
+            text.push(c);
+        }
+        text
+    }
+
     fn tilde(&mut self) -> Token {
         let start = self.offset - 1;
         match self.peek() {
@@ -310,6 +516,12 @@ impl Scanner {
             if c == '?' || c == '+' || c == '*' {
                 self.offset += 1;
                 text.push(c);
+            } else if c == '{' {
+                self.offset += 1;
+                match self.bounded_quantifier() {
+                    Ok(quantifier) => text.push_str(&quantifier.to_string()),
+                    Err(token) => return token,
+                }
             }
         }
         let end = self.offset;
@@ -319,6 +531,58 @@ impl Scanner {
         }
     }
 
+    // {m}, {m,} or {m,n} with m <= n, the opening brace being read
+    fn bounded_quantifier(&mut self) -> Result<Quantifier, Token> {
+        let start = self.offset - 1;
+        let min = self.number();
+        let max = if self.peek() == Some(',') {
+            self.offset += 1;
+            match self.peek() {
+                Some('}') => Some(None),
+                _ => self.number().map(Some),
+            }
+        } else {
+            min.map(Some)
+        };
+        if let (Some(min), Some(max), Some('}')) = (min, max, self.peek()) {
+            self.offset += 1;
+            if max.is_none_or(|max| min <= max) {
+                return Ok(Quantifier::Repeat { min, max });
+            }
+        } else {
+            // the error covers the quantifier up to its closing brace
+            while let Some(c) = self.peek() {
+                if c == '\n' {
+                    break;
+                }
+                self.offset += 1;
+                if c == '}' {
+                    break;
+                }
+            }
+        }
+        let text = self.buffer[start..self.offset].iter().collect::<String>();
+        Err(Token {
+            kind: TokenKind::Error(format!("Invalid quantifier '{}'", text)),
+            span: Span {
+                start,
+                end: self.offset,
+            },
+        })
+    }
+
+    fn number(&mut self) -> Option<usize> {
+        let mut digits = "".to_string();
+        while let Some(c) = self.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            self.offset += 1;
+            digits.push(c);
+        }
+        digits.parse().ok()
+    }
+
     fn read(&mut self) -> Option<char> {
         match self.buffer.get(self.offset) {
             None => {
@@ -428,12 +692,41 @@ mod tests {
         assert_eq!(
             scanner.next().unwrap(),
             Token {
-                kind: TokenKind::LiteralString("\\n".to_string()),
+                kind: TokenKind::LiteralString("\n".to_string()),
                 span: Span { start: 0, end: 4 }
             }
         );
         assert_eq!(scanner.offset, 4);
 
+        // other escape sequences
+        let mut scanner = Scanner::init(r#""\r\t\0\x41\u{e9}\u{1F600}""#);
+        assert_eq!(
+            scanner.next().unwrap(),
+            Token {
+                kind: TokenKind::LiteralString("\r\t\0A\u{e9}\u{1F600}".to_string()),
+                span: Span { start: 0, end: 27 }
+            }
+        );
+
+        // invalid escape sequences, the string being read up to its end
+        let mut scanner = Scanner::init(r#""a\qb\x4" x"#);
+        assert_eq!(
+            scanner.next().unwrap(),
+            Token {
+                kind: TokenKind::Error("Invalid escape sequence '\\q'".to_string()),
+                span: Span { start: 2, end: 4 }
+            }
+        );
+        assert_eq!(scanner.offset, 9);
+        let mut scanner = Scanner::init(r#""\u{110000}""#);
+        assert_eq!(
+            scanner.next().unwrap(),
+            Token {
+                kind: TokenKind::Error("Invalid escape sequence '\\u{110000}'".to_string()),
+                span: Span { start: 1, end: 11 }
+            }
+        );
+
         // quote itself
         let mut scanner = Scanner::init("\"\\\"\"");
         scanner.read();
@@ -485,5 +778,123 @@ mod tests {
             }
         );
         assert_eq!(scanner.offset, 4);
+
+        // bounded quantifier
+        let mut scanner = Scanner::init("[0-9]{2,2}");
+        scanner.read();
+        assert_eq!(
+            scanner.character_class(false),
+            Token {
+                kind: TokenKind::Regex("[0-9]{2}".to_string()),
+                span: Span { start: 0, end: 10 }
+            }
+        );
+    }
+
+    #[test]
+    fn test_bounded_quantifier() {
+        for (s, min, max) in [
+            ("{3}", 3, Some(3)),
+            ("{0,}", 0, None),
+            ("{1,12}", 1, Some(12)),
+        ] {
+            let mut scanner = Scanner::init(s);
+            assert_eq!(
+                scanner.next().unwrap(),
+                Token {
+                    kind: TokenKind::Quantifier(Quantifier::Repeat { min, max }),
+                    span: Span {
+                        start: 0,
+                        end: s.len()
+                    }
+                }
+            );
+        }
+        for s in ["{3,1}", "{a}", "{,2}", "{2"] {
+            let mut scanner = Scanner::init(&format!("{}\n}}", s));
+            assert_eq!(
+                scanner.next().unwrap().kind,
+                TokenKind::Error(format!("Invalid quantifier '{}'", s))
+            );
+            assert_eq!(scanner.offset, s.len());
+        }
+    }
+
+    #[test]
+    fn test_example() {
+        let mut scanner = Scanner::init("#> 1 + 2\n");
+        assert_eq!(
+            scanner.next().unwrap(),
+            Token {
+                kind: TokenKind::Example("1 + 2".to_string()),
+                span: Span { start: 0, end: 8 }
+            }
+        );
+        assert_eq!(scanner.offset, 8);
+    }
+
+    #[test]
+    fn test_prose() {
+        let mut scanner = Scanner::init("# A\n\n\"\"\"\nSome \"text\".\n\"\"\"\n");
+        scanner.next();
+        scanner.next();
+        assert_eq!(
+            scanner.next().unwrap(),
+            Token {
+                kind: TokenKind::Prose("Some \"text\".".to_string()),
+                span: Span { start: 5, end: 25 }
+            }
+        );
+
+        let mut scanner = Scanner::init("# A\n\"\"\"\nSome text\n");
+        scanner.next();
+        scanner.next();
+        assert_eq!(
+            scanner.next().unwrap().kind,
+            TokenKind::Error("Expected a closing '\"\"\"'".to_string())
+        );
+        assert_eq!(
+            Scanner::init("\"\"").next().unwrap().kind,
+            TokenKind::LiteralString("".to_string())
+        );
+
+        // elsewhere, triple quotes are an error at the start of a line, or else literal strings
+        let mut scanner = Scanner::init("# A\na: \"x\"\n\"\"\"\ntext\n\"\"\"\n");
+        assert_eq!(
+            scanner.nth(7).unwrap(),
+            Token {
+                kind: TokenKind::Error("A prose block must follow a section heading".to_string()),
+                span: Span { start: 11, end: 23 }
+            }
+        );
+        let mut scanner = Scanner::init("# A\na: \"\"\"x\"\n");
+        assert_eq!(
+            scanner.nth(5).unwrap().kind,
+            TokenKind::LiteralString("".to_string())
+        );
+        assert_eq!(
+            scanner.next().unwrap().kind,
+            TokenKind::LiteralString("x".to_string())
+        );
+    }
+
+    #[test]
+    fn test_doc() {
+        let mut scanner = Scanner::init("#:A number\n#:\n");
+        assert_eq!(
+            scanner.next().unwrap(),
+            Token {
+                kind: TokenKind::Doc("A number".to_string()),
+                span: Span { start: 0, end: 10 }
+            }
+        );
+        scanner.next();
+        assert_eq!(
+            scanner.next().unwrap(),
+            Token {
+                kind: TokenKind::Doc("".to_string()),
+                span: Span { start: 11, end: 13 }
+            }
+        );
     }
 }
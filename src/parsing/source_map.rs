@@ -0,0 +1,363 @@
+use super::{Scanner, TokenKind};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Text of a grammar made of several files.
+///
+/// An included file is inserted after the line of its include directive, each file being read once,
+/// and the spans of the grammar parsed from the text can be located in their file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceMap {
+    pub text: String,
+    // length of the text in chars
+    len: usize,
+    files: Vec<SourceFile>,
+    // parts of the text copied from the files, in order
+    segments: Vec<Segment>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct SourceFile {
+    // None for the standard input
+    path: Option<PathBuf>,
+    text: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Segment {
+    // offset in the text
+    start: usize,
+    // index of the file, and offset in the file
+    file: usize,
+    offset: usize,
+}
+
+/// Position in a file, lines and columns starting at 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub path: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceError {
+    pub location: Location,
+    pub message: String,
+}
+
+impl SourceMap {
+    /// Returns the source made of the main file only (`None` for the standard input).
+    pub fn new(path: Option<&Path>, text: &str) -> SourceMap {
+        let mut source = SourceMap {
+            text: "".to_string(),
+            len: 0,
+            files: vec![SourceFile {
+                path: path.map(Path::to_path_buf),
+                text: text.to_string(),
+            }],
+            segments: vec![],
+        };
+        source.copy(0, 0, text.chars().count());
+        source
+    }
+
+    /// Inserts the files included by the main file, recursively.
+    ///
+    /// Paths are relative to the including file (to the current directory for the standard input).
+    /// A file already included is skipped, a file including itself is an error.
+    pub fn include(&mut self) -> Result<(), SourceError> {
+        let mut stack = vec![];
+        let mut loaded = HashSet::new();
+        if let Some(path) = &self.files[0].path {
+            if let Ok(canonical) = path.canonicalize() {
+                loaded.insert(canonical.clone());
+                stack.push((canonical, path.clone()));
+            }
+        }
+        self.text.clear();
+        self.len = 0;
+        self.segments.clear();
+        self.include_from(0, &mut stack, &mut loaded)
+    }
+
+    // copies the file to the text, inserting its included files
+    // `stack` holds the files being included (canonical and displayed paths)
+    fn include_from(
+        &mut self,
+        index: usize,
+        stack: &mut Vec<(PathBuf, PathBuf)>,
+        loaded: &mut HashSet<PathBuf>,
+    ) -> Result<(), SourceError> {
+        let file = self.files[index].clone();
+        let chars = file.text.chars().collect::<Vec<char>>();
+        let dir = match file.path.as_ref().and_then(|path| path.parent()) {
+            None => PathBuf::new(),
+            Some(dir) => dir.to_path_buf(),
+        };
+        // offset of the file copied so far
+        let mut copied = 0;
+        for (offset, path) in includes(&file.text) {
+            let location = self.file_location(index, offset);
+            let error = |message: String| SourceError {
+                location: location.clone(),
+                message,
+            };
+            let path = dir.join(path);
+            let canonical = path
+                .canonicalize()
+                .map_err(|e| error(format!("unable to read {}: {}", path.display(), e)))?;
+            if let Some(i) = stack.iter().position(|(other, _)| *other == canonical) {
+                let mut paths = stack[i..]
+                    .iter()
+                    .map(|(_, path)| path.display().to_string())
+                    .collect::<Vec<String>>();
+                paths.push(path.display().to_string());
+                return Err(error(format!("include cycle: {}", paths.join(" -> "))));
+            }
+            if !loaded.insert(canonical.clone()) {
+                continue;
+            }
+            let text = std::fs::read_to_string(&path)
+                .map_err(|e| error(format!("unable to read {}: {}", path.display(), e)))?;
+            // the included file follows the line of the directive
+            let end = match chars[offset..].iter().position(|c| *c == '\n') {
+                None => chars.len(),
+                Some(i) => offset + i + 1,
+            };
+            self.copy(index, copied, end);
+            copied = end;
+            self.files.push(SourceFile {
+                path: Some(path.clone()),
+                text,
+            });
+            stack.push((canonical, path));
+            self.include_from(self.files.len() - 1, stack, loaded)?;
+            stack.pop();
+        }
+        self.copy(index, copied, chars.len());
+        Ok(())
+    }
+
+    // appends the chars of the file between the two offsets to the text
+    fn copy(&mut self, index: usize, start: usize, end: usize) {
+        if start == end {
+            return;
+        }
+        // each file starts on a new line, and so does the rest of a file following an included file
+        if !self.text.is_empty() && !self.text.ends_with('\n') {
+            self.text.push('\n');
+            self.len += 1;
+        }
+        self.segments.push(Segment {
+            start: self.len,
+            file: index,
+            offset: start,
+        });
+        self.text
+            .extend(self.files[index].text.chars().skip(start).take(end - start));
+        self.len += end - start;
+    }
+
+    /// Returns the file, line and column of an offset of the text.
+    ///
+    /// The path is given only if the text is made of several files.
+    pub fn location(&self, offset: usize) -> Location {
+        let (index, offset) = match self
+            .segments
+            .iter()
+            .rev()
+            .find(|segment| segment.start <= offset)
+        {
+            None => (0, 0),
+            Some(segment) => (segment.file, segment.offset + offset - segment.start),
+        };
+        let mut location = self.file_location(index, offset);
+        if self.files.len() == 1 {
+            location.path = None;
+        }
+        location
+    }
+
+    // location of an offset of a file
+    fn file_location(&self, index: usize, offset: usize) -> Location {
+        let file = &self.files[index];
+        let mut line = 1;
+        let mut column = 1;
+        for c in file.text.chars().take(offset) {
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        Location {
+            path: file.path.clone(),
+            line,
+            column,
+        }
+    }
+}
+
+// offsets and paths of the include directives of a file
+fn includes(text: &str) -> Vec<(usize, String)> {
+    let tokens = Scanner::init(text)
+        .filter(|token| !matches!(token.kind, TokenKind::Whitespace(_)))
+        .collect::<Vec<_>>();
+    tokens
+        .windows(2)
+        .filter_map(|tokens| match (&tokens[0].kind, &tokens[1].kind) {
+            (TokenKind::Include, TokenKind::LiteralString(path)) => {
+                Some((tokens[0].span.start, path.clone()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{}:", path.display())?;
+        }
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    // a directory with the given files, removed beforehand
+    fn files(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("grammar-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("lexical")).unwrap();
+        for (path, text) in files {
+            std::fs::write(dir.join(path), text).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_include() {
+        let dir = files(
+            "include",
+            &[
+                (
+                    "main.grammar",
+                    "# Main\na: expr\n@include \"expr.grammar\"\n@include \"lexical/number.grammar\"",
+                ),
+                (
+                    "expr.grammar",
+                    "@include \"lexical/number.grammar\"\n# Expr\nexpr: number\n",
+                ),
+                ("lexical/number.grammar", "# Number\nnumber: digit"),
+            ],
+        );
+        let main = dir.join("main.grammar");
+        let text = std::fs::read_to_string(&main).unwrap();
+        let mut source = SourceMap::new(Some(&main), &text);
+        source.include().unwrap();
+        assert_eq!(
+            source.text,
+            "# Main\na: expr\n@include \"expr.grammar\"\n\
+             @include \"lexical/number.grammar\"\n\
+             # Number\nnumber: digit\n\
+             # Expr\nexpr: number\n\
+             @include \"lexical/number.grammar\""
+        );
+
+        let g = parse(&source.text).unwrap();
+        let ids = g
+            .get_rules()
+            .into_iter()
+            .map(|rule| rule.id)
+            .collect::<Vec<String>>();
+        assert_eq!(ids, vec!["a", "number", "expr"]);
+        let errors = g.validate();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            source.location(errors[0].span.start),
+            Location {
+                path: Some(dir.join("lexical/number.grammar")),
+                line: 2,
+                column: 9,
+            }
+        );
+        assert_eq!(
+            source.location(source.text.find("expr:").unwrap()),
+            Location {
+                path: Some(dir.join("expr.grammar")),
+                line: 3,
+                column: 1,
+            }
+        );
+        assert_eq!(
+            source.location(source.text.rfind("@include").unwrap()),
+            Location {
+                path: Some(main),
+                line: 4,
+                column: 1,
+            }
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_location() {
+        // the path is not given for a single file
+        let mut source = SourceMap::new(Some(Path::new("a.grammar")), "# A\na: b\n");
+        source.include().unwrap();
+        assert_eq!(
+            source.location(6),
+            Location {
+                path: None,
+                line: 2,
+                column: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn test_include_error() {
+        let dir = files(
+            "include-error",
+            &[
+                ("a.grammar", "# A\n@include \"b.grammar\"\n"),
+                ("b.grammar", "@include \"a.grammar\"\n"),
+                ("c.grammar", "@include \"d.grammar\"\n"),
+            ],
+        );
+        let a = dir.join("a.grammar");
+        let mut source = SourceMap::new(Some(&a), "# A\n@include \"b.grammar\"\n");
+        assert_eq!(
+            source.include(),
+            Err(SourceError {
+                location: Location {
+                    path: Some(dir.join("b.grammar")),
+                    line: 1,
+                    column: 1,
+                },
+                message: format!(
+                    "include cycle: {} -> {} -> {}",
+                    a.display(),
+                    dir.join("b.grammar").display(),
+                    a.display()
+                ),
+            })
+        );
+
+        let c = dir.join("c.grammar");
+        let mut source = SourceMap::new(Some(&c), "@include \"d.grammar\"\n");
+        let error = source.include().unwrap_err();
+        assert!(error.message.starts_with(&format!(
+            "unable to read {}",
+            dir.join("d.grammar").display()
+        )));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -13,10 +13,14 @@ pub enum TokenKind {
     LeftParenthesis,
     RightParenthesis,
     Tilde,
+    Include,
     Quantifier(Quantifier),
     Whitespace(String),
     Newline(String),
     Comment(String),
+    Example(String),
+    Doc(String),
+    Prose(String),
     Identifier(String),
     LiteralString(String),
     Regex(String),
@@ -32,10 +36,14 @@ impl TokenKind {
             TokenKind::LeftParenthesis => "(".to_string(),
             TokenKind::RightParenthesis => ")".to_string(),
             TokenKind::Tilde => "~".to_string(),
+            TokenKind::Include => "@include".to_string(),
             TokenKind::Quantifier(quantifier) => quantifier.to_string(),
             TokenKind::Whitespace(s) => s.to_string(),
             TokenKind::Newline(s) => s.to_string(),
             TokenKind::Comment(s) => s.to_string(),
+            TokenKind::Example(s) => s.to_string(),
+            TokenKind::Doc(s) => s.to_string(),
+            TokenKind::Prose(s) => s.to_string(),
             TokenKind::Identifier(s) => s.to_string(),
             TokenKind::LiteralString(s) => s.to_string(),
             TokenKind::Regex(s) => s.to_string(),